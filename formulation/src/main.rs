@@ -1,6 +1,15 @@
+#[cfg(feature = "highs")]
 mod higgs_solve;
+#[cfg(feature = "highs")]
 mod parse;
 
+#[cfg(not(feature = "highs"))]
+fn main() {
+    eprintln!("this program solves the MIP with HiGHS, build it with the highs feature");
+    std::process::exit(2);
+}
+
+#[cfg(feature = "highs")]
 fn main() {
     let args = std::env::args().collect::<Vec<String>>();
 
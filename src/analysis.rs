@@ -0,0 +1,233 @@
+use std::fmt;
+
+use crate::parse::TkpInstance;
+
+// Resumo de uma distribuição de valores
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p90: f64,
+}
+
+impl Summary {
+    fn of(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Summary::default();
+        }
+
+        values.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+
+        Summary {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            median: percentile(0.5),
+            p90: percentile(0.9),
+        }
+    }
+}
+
+// Caracteristicas de uma instancia, usadas para escolher os parametros dos solvers
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct InstanceStats {
+    pub name: String,
+    pub order_count: usize,
+    pub capacity: u32,
+    // periodos entre o primeiro inicio e o ultimo fim
+    pub horizon: u64,
+    // valores distintos de inicio e fim
+    pub event_points: usize,
+    // ordens ativas por periodo
+    pub max_overlap: usize,
+    pub avg_overlap: f64,
+    // demanda de todas as ordens ativas / capacidade, por periodo
+    pub max_load_ratio: f64,
+    pub avg_load_ratio: f64,
+    // fração dos periodos em que a restrição de capacidade pode ficar ativa
+    pub binding_share: f64,
+    // fim - inicio
+    pub duration: Summary,
+    pub demand: Summary,
+    pub profit: Summary,
+    // subproblemas independentes, com a mesma regra de `TkpInstance::components`:
+    // intervalos que se tocam só onde a capacidade nunca estoura não contam como ligados
+    pub components: usize,
+}
+
+impl TkpInstance {
+    pub fn analyze(&self) -> InstanceStats {
+        let first_period = self
+            .orders
+            .iter()
+            .map(|o| o.start as u64)
+            .min()
+            .unwrap_or(0);
+        let last_period = self.orders.iter().map(|o| o.end as u64).max().unwrap_or(0);
+        let horizon = if self.orders.is_empty() {
+            0
+        } else {
+            last_period.saturating_sub(first_period) + 1
+        };
+
+        // diferenças acumuladas de contagem e demanda ativa em cada segmento do eixo
+        // comprimido; cada segmento pesa pelo numero de periodos que cobre
+        let timeline = self.timeline();
+        let mut active_delta = vec![0i64; timeline.points.len()];
+        let mut demand_delta = vec![0i64; timeline.points.len()];
+        for (order, span) in self.orders.iter().zip(&timeline.spans) {
+            if span.is_empty() {
+                continue;
+            }
+            active_delta[span.start] += 1;
+            active_delta[span.end] -= 1;
+            demand_delta[span.start] += order.demand as i64;
+            demand_delta[span.end] -= order.demand as i64;
+        }
+
+        let (mut active, mut demand) = (0i64, 0i64);
+        let (mut max_overlap, mut total_overlap) = (0usize, 0u64);
+        let (mut max_load_ratio, mut total_load_ratio, mut binding) = (0f64, 0f64, 0u64);
+        for k in 0..timeline.segment_count() {
+            active += active_delta[k];
+            demand += demand_delta[k];
+
+            let length = timeline.segment_length(k);
+            let load_ratio = demand as f64 / self.capacity.max(1) as f64;
+            max_overlap = max_overlap.max(active as usize);
+            total_overlap += active as u64 * length;
+            max_load_ratio = max_load_ratio.max(load_ratio);
+            total_load_ratio += load_ratio * length as f64;
+            if demand > self.capacity as i64 {
+                binding += length;
+            }
+        }
+
+        let mut event_points: Vec<u32> =
+            self.orders.iter().flat_map(|o| [o.start, o.end]).collect();
+        event_points.sort_unstable();
+        event_points.dedup();
+
+        let periods = (horizon as f64).max(1.0);
+        InstanceStats {
+            name: self.name.clone(),
+            order_count: self.orders.len(),
+            capacity: self.capacity,
+            horizon,
+            event_points: event_points.len(),
+            max_overlap,
+            avg_overlap: total_overlap as f64 / periods,
+            max_load_ratio,
+            avg_load_ratio: total_load_ratio / periods,
+            binding_share: binding as f64 / periods,
+            duration: Summary::of(
+                self.orders
+                    .iter()
+                    .map(|o| o.end.saturating_sub(o.start) as f64)
+                    .collect(),
+            ),
+            demand: Summary::of(self.orders.iter().map(|o| o.demand as f64).collect()),
+            profit: Summary::of(self.orders.iter().map(|o| o.profit as f64).collect()),
+            components: self.components().len(),
+        }
+    }
+}
+
+impl InstanceStats {
+    pub const TABLE_HEADER: &'static str = "name\torders\tcapacity\thorizon\tevent_points\tmax_overlap\tavg_overlap\tmax_load_ratio\tavg_load_ratio\tbinding_share\tduration_mean\tduration_p90\tdemand_mean\tprofit_mean\tcomponents";
+
+    // Linha tsv com as mesmas colunas de TABLE_HEADER
+    pub fn table_row(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{:.2}\t{:.2}\t{:.2}\t{:.3}\t{:.2}\t{:.0}\t{:.2}\t{:.2}\t{}",
+            self.name,
+            self.order_count,
+            self.capacity,
+            self.horizon,
+            self.event_points,
+            self.max_overlap,
+            self.avg_overlap,
+            self.max_load_ratio,
+            self.avg_load_ratio,
+            self.binding_share,
+            self.duration.mean,
+            self.duration.p90,
+            self.demand.mean,
+            self.profit.mean,
+            self.components
+        )
+    }
+}
+
+impl fmt::Display for InstanceStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "instance          {}", self.name)?;
+        writeln!(f, "orders            {}", self.order_count)?;
+        writeln!(f, "capacity          {}", self.capacity)?;
+        writeln!(f, "horizon           {}", self.horizon)?;
+        writeln!(f, "event points      {}", self.event_points)?;
+        writeln!(
+            f,
+            "overlap           max {}, avg {:.2}",
+            self.max_overlap, self.avg_overlap
+        )?;
+        writeln!(
+            f,
+            "load ratio        max {:.2}, avg {:.2}",
+            self.max_load_ratio, self.avg_load_ratio
+        )?;
+        writeln!(f, "binding periods   {:.1}%", self.binding_share * 100.0)?;
+        for (name, summary) in [
+            ("duration", &self.duration),
+            ("demand", &self.demand),
+            ("profit", &self.profit),
+        ] {
+            writeln!(
+                f,
+                "{:<17} min {}, median {}, mean {:.2}, p90 {}, max {}",
+                name, summary.min, summary.median, summary.mean, summary.p90, summary.max
+            )?;
+        }
+        writeln!(f, "components        {}", self.components)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_weight_segments_by_length() {
+        let instance = TkpInstance::parse_from_str("2\n10\n5 6 1 4\n5 8 3 10\n", "test").unwrap();
+        let stats = instance.analyze();
+        assert_eq!(stats.horizon, 10);
+        assert_eq!(stats.max_overlap, 2);
+        assert!((stats.avg_overlap - 1.2).abs() < 1e-9);
+        assert!((stats.max_load_ratio - 1.4).abs() < 1e-9);
+        assert!((stats.binding_share - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn components_follow_decompose() {
+        // as tres ordens se encadeiam, mas só 1 e 2 juntas passam da capacidade
+        let instance =
+            TkpInstance::parse_from_str("3\n10\n5 4 1 4\n5 4 3 8\n5 7 6 9\n", "test").unwrap();
+        let stats = instance.analyze();
+        assert_eq!(stats.components, 2);
+        assert_eq!(stats.components, instance.components().len());
+    }
+
+    #[test]
+    fn full_u32_horizon_does_not_overflow() {
+        let instance = TkpInstance::parse_from_str("1\n1\n1 1 0 4294967295\n", "test").unwrap();
+        let stats = instance.analyze();
+        assert_eq!(stats.horizon, 1 << 32);
+        assert_eq!(stats.max_overlap, 1);
+        assert!((stats.avg_overlap - 1.0).abs() < 1e-9);
+    }
+}
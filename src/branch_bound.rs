@@ -0,0 +1,552 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    lagrangian::LagrangianOptions, parse::TkpInstance, tabu_search::Solution,
+    validate::ValidationError,
+};
+
+// Branch and bound exato em Rust puro, sem o HiGHS. As ordens são fixadas uma a uma numa
+// ordem fixa (maior lucro reduzido lagrangiano primeiro) e cada nó tem como limite o menor
+// entre:
+// - o limite lagrangiano com os multiplicadores da raiz, que sai em O(1) por nó;
+// - a mochila fracionaria por restrição de capacidade, com o lucro de cada ordem dividido
+//   entre as restrições em que ela aparece e a capacidade já usada pelas ordens fixadas.
+// A busca tabu dá o incumbente inicial
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeSelection {
+    // nó de maior limite primeiro
+    BestFirst,
+    // profundidade primeiro, ramo com a ordem selecionada antes
+    DepthFirst,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchBoundOptions {
+    pub node_selection: NodeSelection,
+    pub node_limit: u64,
+    pub time_limit: Option<Duration>,
+    // iterações da busca tabu que gera o incumbente inicial (0 desliga)
+    pub tabu_iterations: usize,
+    pub random_seed: u64,
+    pub lagrangian: LagrangianOptions,
+}
+
+impl Default for BranchBoundOptions {
+    fn default() -> Self {
+        Self {
+            node_selection: NodeSelection::BestFirst,
+            node_limit: 1_000_000,
+            time_limit: None,
+            tabu_iterations: 5000,
+            random_seed: 0,
+            lagrangian: LagrangianOptions::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchBoundStatus {
+    Optimal,
+    NodeLimit,
+    TimeLimit,
+}
+
+impl fmt::Display for BranchBoundStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BranchBoundStatus::Optimal => write!(f, "optimal"),
+            BranchBoundStatus::NodeLimit => write!(f, "node limit"),
+            BranchBoundStatus::TimeLimit => write!(f, "time limit"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchBoundResult {
+    pub status: BranchBoundStatus,
+    pub solution: Solution,
+    // lucro do incumbente inicial (busca tabu ou lagrangiana reparada)
+    pub initial_profit: u32,
+    // igual ao lucro da solução quando otimo, senão o maior limite ainda aberto
+    pub upper_bound: f64,
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+impl BranchBoundResult {
+    pub fn is_optimal(&self) -> bool {
+        self.status == BranchBoundStatus::Optimal
+    }
+}
+
+// Nó da arvore: as `depth` primeiras ordens de `branch_order` estão fixadas, e as
+// selecionadas formam a lista encadeada que termina em `choice` (como em `dp_solve`)
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    depth: usize,
+    bound: f64,
+    profit: u64,
+    // soma dos lucros reduzidos das ordens selecionadas
+    reduced: f64,
+    choice: Option<usize>,
+}
+
+// Ordem do heap: maior limite, e no empate o nó mais fundo
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound
+            .total_cmp(&other.bound)
+            .then(self.depth.cmp(&other.depth))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+enum Frontier {
+    Heap(BinaryHeap<Node>),
+    Stack(Vec<Node>),
+}
+
+impl Frontier {
+    fn push(&mut self, node: Node) {
+        match self {
+            Frontier::Heap(heap) => heap.push(node),
+            Frontier::Stack(stack) => stack.push(node),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Node> {
+        match self {
+            Frontier::Heap(heap) => heap.pop(),
+            Frontier::Stack(stack) => stack.pop(),
+        }
+    }
+
+    fn max_bound(&self) -> Option<f64> {
+        let nodes = match self {
+            Frontier::Heap(heap) => return heap.peek().map(|node| node.bound),
+            Frontier::Stack(stack) => stack,
+        };
+        nodes.iter().map(|node| node.bound).max_by(f64::total_cmp)
+    }
+}
+
+// Dados fixos da busca, calculados na raiz
+struct Tree<'a> {
+    instance: &'a TkpInstance,
+    capacity: u64,
+    branch_order: Vec<usize>,
+    // posição de cada ordem em `branch_order`
+    position: Vec<usize>,
+    // restrições de cada ordem
+    order_rows: Vec<Vec<usize>>,
+    // ordens de cada restrição, por lucro dividido / demanda decrescente
+    row_orders: Vec<Vec<usize>>,
+    // lucro de cada ordem dividido entre as suas restrições
+    split_profit: Vec<f64>,
+    // ordens fora de qualquer restrição, sempre cabem
+    free_orders: Vec<usize>,
+    reduced_profit: Vec<f64>,
+    // C sum u
+    lagrangian_constant: f64,
+    // soma de max(0, lucro reduzido) das ordens de `branch_order[d..]`
+    positive_suffix: Vec<f64>,
+}
+
+impl TkpInstance {
+    pub fn branch_and_bound(
+        &self,
+        options: &BranchBoundOptions,
+    ) -> Result<BranchBoundResult, ValidationError> {
+        self.check_valid()?;
+
+        let now = Instant::now();
+        let presolved = self.presolve();
+        let instance = &presolved.instance;
+        let fixed_profit = presolved.fixed_profit as u64;
+        if presolved.is_solved() {
+            return Ok(BranchBoundResult {
+                status: BranchBoundStatus::Optimal,
+                solution: presolved.expand(self, &[]),
+                initial_profit: presolved.fixed_profit,
+                upper_bound: fixed_profit as f64,
+                nodes: 0,
+                time: now.elapsed(),
+            });
+        }
+
+        let lagrangian = instance.lagrangian(&options.lagrangian)?;
+        let mut incumbent = lagrangian.solution.selected_orders.clone();
+        let mut incumbent_profit = lagrangian.solution.total_profit as u64;
+        if options.tabu_iterations > 0 {
+            let tabu = instance.presolved_tabu_search(
+                options.tabu_iterations,
+                10,
+                50,
+                options.random_seed,
+                false,
+            );
+            if tabu.total_profit as u64 > incumbent_profit {
+                incumbent_profit = tabu.total_profit as u64;
+                incumbent = tabu.selected_orders;
+            }
+        }
+        let initial_profit = (incumbent_profit + fixed_profit) as u32;
+
+        let tree = Tree::new(instance, &lagrangian.multipliers);
+        let root = Node {
+            depth: 0,
+            bound: lagrangian.upper_bound,
+            profit: 0,
+            reduced: 0.0,
+            choice: None,
+        };
+        let mut frontier = match options.node_selection {
+            NodeSelection::BestFirst => Frontier::Heap(BinaryHeap::new()),
+            NodeSelection::DepthFirst => Frontier::Stack(Vec::new()),
+        };
+        frontier.push(root);
+
+        // decisões de seleção: (ordem, decisão anterior)
+        let mut choices: Vec<(usize, Option<usize>)> = Vec::new();
+        let mut path = Vec::new();
+        let mut load = vec![0u64; tree.row_orders.len()];
+        let mut nodes = 0;
+        let status = loop {
+            if options
+                .time_limit
+                .is_some_and(|limit| now.elapsed() >= limit)
+            {
+                break BranchBoundStatus::TimeLimit;
+            }
+            if nodes >= options.node_limit {
+                break BranchBoundStatus::NodeLimit;
+            }
+            let Some(node) = frontier.pop() else {
+                break BranchBoundStatus::Optimal;
+            };
+            if !improves(node.bound, incumbent_profit) {
+                continue;
+            }
+            nodes += 1;
+
+            path.clear();
+            let mut choice = node.choice;
+            while let Some(c) = choice {
+                let (order, previous) = choices[c];
+                path.push(order);
+                choice = previous;
+            }
+            tree.add_load(&mut load, &path, true);
+
+            if node.depth == tree.branch_order.len() {
+                if node.profit > incumbent_profit {
+                    incumbent_profit = node.profit;
+                    incumbent = vec![false; instance.orders.len()];
+                    for &order in &path {
+                        incumbent[order] = true;
+                    }
+                }
+            } else {
+                let order = tree.branch_order[node.depth];
+                let fits = tree.order_rows[order]
+                    .iter()
+                    .all(|&r| load[r] + instance.orders[order].demand as u64 <= tree.capacity);
+
+                let without = tree.child(node, false, &load, incumbent_profit);
+                let mut with = None;
+                if fits {
+                    tree.add_load(&mut load, &[order], true);
+                    with = tree.child(node, true, &load, incumbent_profit);
+                    tree.add_load(&mut load, &[order], false);
+                }
+                if let Some(child) = &mut with {
+                    choices.push((order, node.choice));
+                    child.choice = Some(choices.len() - 1);
+                }
+                // na pilha o ramo com a ordem sai primeiro
+                for child in [without, with].into_iter().flatten() {
+                    frontier.push(child);
+                }
+            }
+
+            tree.add_load(&mut load, &path, false);
+        };
+
+        let upper_bound = match status {
+            BranchBoundStatus::Optimal => incumbent_profit as f64,
+            _ => frontier
+                .max_bound()
+                .map_or(incumbent_profit as f64, |bound| {
+                    bound.max(incumbent_profit as f64)
+                }),
+        };
+        Ok(BranchBoundResult {
+            status,
+            solution: presolved.expand(self, &incumbent),
+            initial_profit,
+            upper_bound: upper_bound + fixed_profit as f64,
+            nodes,
+            time: now.elapsed(),
+        })
+    }
+}
+
+// O lucro é inteiro, então um limite só interessa se o seu piso passa do incumbente
+fn improves(bound: f64, incumbent_profit: u64) -> bool {
+    (bound + 1e-6).floor() > incumbent_profit as f64
+}
+
+impl<'a> Tree<'a> {
+    fn new(instance: &'a TkpInstance, multipliers: &[f64]) -> Self {
+        let (rows, _) = instance.capacity_rows();
+        let mut order_rows = vec![Vec::new(); instance.orders.len()];
+        for (r, row) in rows.iter().enumerate() {
+            for &i in &row.orders {
+                order_rows[i].push(r);
+            }
+        }
+
+        let split_profit: Vec<f64> = instance
+            .orders
+            .iter()
+            .zip(&order_rows)
+            .map(|(order, rows)| order.profit as f64 / rows.len().max(1) as f64)
+            .collect();
+        let ratio = |i: usize| split_profit[i] / instance.orders[i].demand as f64;
+        let row_orders = rows
+            .into_iter()
+            .map(|row| {
+                let mut orders = row.orders;
+                orders.sort_by(|&a, &b| ratio(b).total_cmp(&ratio(a)));
+                orders
+            })
+            .collect();
+        let free_orders = (0..instance.orders.len())
+            .filter(|&i| order_rows[i].is_empty())
+            .collect();
+
+        let reduced_profit: Vec<f64> = instance
+            .orders
+            .iter()
+            .zip(&order_rows)
+            .map(|(order, rows)| {
+                let price: f64 = rows.iter().map(|&r| multipliers[r]).sum();
+                order.profit as f64 - order.demand as f64 * price
+            })
+            .collect();
+        let mut branch_order: Vec<usize> = (0..instance.orders.len()).collect();
+        branch_order.sort_by(|&a, &b| reduced_profit[b].total_cmp(&reduced_profit[a]));
+        let mut position = vec![0; instance.orders.len()];
+        for (k, &i) in branch_order.iter().enumerate() {
+            position[i] = k;
+        }
+
+        let mut positive_suffix = vec![0.0; branch_order.len() + 1];
+        for k in (0..branch_order.len()).rev() {
+            positive_suffix[k] = positive_suffix[k + 1] + reduced_profit[branch_order[k]].max(0.0);
+        }
+
+        Self {
+            instance,
+            capacity: instance.capacity as u64,
+            branch_order,
+            position,
+            order_rows,
+            row_orders,
+            split_profit,
+            free_orders,
+            reduced_profit,
+            lagrangian_constant: instance.capacity as f64 * multipliers.iter().sum::<f64>(),
+            positive_suffix,
+        }
+    }
+
+    // Soma (ou subtrai) a demanda de `orders` nas suas restrições
+    fn add_load(&self, load: &mut [u64], orders: &[usize], add: bool) {
+        for &i in orders {
+            let demand = self.instance.orders[i].demand as u64;
+            for &r in &self.order_rows[i] {
+                if add {
+                    load[r] += demand;
+                } else {
+                    load[r] -= demand;
+                }
+            }
+        }
+    }
+
+    // Filho de `node` fixando a proxima ordem, com `load` já incluindo a ordem se
+    // `select`. `None` se o limite não passa do incumbente
+    fn child(&self, node: Node, select: bool, load: &[u64], incumbent_profit: u64) -> Option<Node> {
+        let order = self.branch_order[node.depth];
+        let depth = node.depth + 1;
+        let (profit, reduced) = if select {
+            (
+                node.profit + self.instance.orders[order].profit as u64,
+                node.reduced + self.reduced_profit[order],
+            )
+        } else {
+            (node.profit, node.reduced)
+        };
+
+        let lagrangian = self.lagrangian_constant + reduced + self.positive_suffix[depth];
+        if !improves(lagrangian, incumbent_profit) {
+            return None;
+        }
+        let bound = lagrangian.min(profit as f64 + self.knapsack_bound(depth, load));
+        improves(bound, incumbent_profit).then_some(Node {
+            depth,
+            bound,
+            profit,
+            reduced,
+            choice: node.choice,
+        })
+    }
+
+    // Soma, em cada restrição, da mochila fracionaria das ordens ainda livres com a
+    // capacidade que sobrou, mais as ordens livres fora das restrições
+    fn knapsack_bound(&self, depth: usize, load: &[u64]) -> f64 {
+        let is_free = |i: usize| self.position[i] >= depth;
+        let mut bound: f64 = self
+            .free_orders
+            .iter()
+            .filter(|&&i| is_free(i))
+            .map(|&i| self.instance.orders[i].profit as f64)
+            .sum();
+
+        for (orders, &used) in self.row_orders.iter().zip(load) {
+            let mut residual = self.capacity - used;
+            for &i in orders.iter().filter(|&&i| is_free(i)) {
+                let demand = self.instance.orders[i].demand as u64;
+                if demand <= residual {
+                    residual -= demand;
+                    bound += self.split_profit[i];
+                } else {
+                    bound += self.split_profit[i] * residual as f64 / demand as f64;
+                    break;
+                }
+            }
+        }
+        bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lagrangian::LagrangianOptions,
+        test_instances::{optimum, random_instances},
+    };
+
+    use super::*;
+
+    #[test]
+    fn branch_and_bound_matches_dp() {
+        for instance in random_instances(3, 200, false) {
+            let expected = optimum(&instance);
+            for node_selection in [NodeSelection::BestFirst, NodeSelection::DepthFirst] {
+                let options = BranchBoundOptions {
+                    node_selection,
+                    tabu_iterations: 50,
+                    ..BranchBoundOptions::default()
+                };
+                let result = instance.branch_and_bound(&options).unwrap();
+                assert!(result.is_optimal());
+                assert!(result.solution.is_feasible);
+                assert_eq!(result.solution.total_profit, expected, "{:?}", instance);
+            }
+        }
+    }
+
+    // lagrangiana curta e sem busca tabu: o incumbente inicial é fraco e a arvore
+    // precisa de nós, então os limites pequenos cortam a busca
+    fn truncated_options(node_limit: u64, time_limit: Option<Duration>) -> BranchBoundOptions {
+        BranchBoundOptions {
+            node_limit,
+            time_limit,
+            tabu_iterations: 0,
+            lagrangian: LagrangianOptions {
+                iterations: 3,
+                ..LagrangianOptions::default()
+            },
+            ..BranchBoundOptions::default()
+        }
+    }
+
+    // uma busca interrompida devolve um incumbente viavel e um limite que ainda cobre o otimo
+    fn check_truncated(instance: &TkpInstance, result: &BranchBoundResult, expected: u32) {
+        assert!(result.solution.is_feasible);
+        assert!(result.solution.total_profit <= expected);
+        assert!(
+            result.upper_bound + 1e-6 >= expected as f64,
+            "{:?}: bound {} below optimum {}",
+            instance,
+            result.upper_bound,
+            expected
+        );
+        assert!(result.upper_bound + 1e-6 >= result.solution.total_profit as f64);
+    }
+
+    #[test]
+    fn node_limit_reports_valid_bound() {
+        let mut truncated = 0;
+        for instance in random_instances(8, 300, false) {
+            let expected = optimum(&instance);
+            for node_selection in [NodeSelection::BestFirst, NodeSelection::DepthFirst] {
+                for node_limit in [0, 1, 3, 10] {
+                    let options = BranchBoundOptions {
+                        node_selection,
+                        ..truncated_options(node_limit, None)
+                    };
+                    let result = instance.branch_and_bound(&options).unwrap();
+                    assert!(result.nodes <= node_limit);
+                    match result.status {
+                        BranchBoundStatus::Optimal => {
+                            assert_eq!(result.solution.total_profit, expected)
+                        }
+                        BranchBoundStatus::NodeLimit => {
+                            truncated += 1;
+                            check_truncated(&instance, &result, expected);
+                        }
+                        BranchBoundStatus::TimeLimit => panic!("no time limit was set"),
+                    }
+                }
+            }
+        }
+        assert!(truncated > 0);
+    }
+
+    #[test]
+    fn time_limit_stops_before_the_first_node() {
+        for instance in random_instances(9, 100, false) {
+            let expected = optimum(&instance);
+            let options = truncated_options(u64::MAX, Some(Duration::ZERO));
+            let result = instance.branch_and_bound(&options).unwrap();
+            assert_eq!(result.nodes, 0);
+            match result.status {
+                // o presolve resolveu a instancia inteira
+                BranchBoundStatus::Optimal => assert_eq!(result.solution.total_profit, expected),
+                BranchBoundStatus::TimeLimit => check_truncated(&instance, &result, expected),
+                BranchBoundStatus::NodeLimit => panic!("no node limit was set"),
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::parse::TkpInstance;
+
+// Restrição de capacidade sobre um conjunto de ordens ativas ao mesmo tempo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityRow {
+    // periodo original em que o conjunto está ativo (inicio de alguma ordem)
+    pub period: u64,
+    pub orders: Vec<usize>,
+}
+
+// Numero de restrições de capacidade em cada formulação
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RowCounts {
+    // uma por periodo do horizonte (formulação original)
+    pub per_period: u64,
+    // uma por segmento do eixo comprimido
+    pub per_segment: usize,
+    // uma por clique maximal
+    pub cliques: usize,
+}
+
+impl TkpInstance {
+    // Em um grafo de intervalos as cliques maximais são os conjuntos de ordens ativas
+    // no inicio de alguma ordem; basta uma restrição por clique maximal, já que qualquer
+    // conjunto de ordens ativas em um periodo está contido em uma delas.
+    // Cliques cuja demanda total cabe na capacidade não precisam de restrição
+    pub fn capacity_rows(&self) -> (Vec<CapacityRow>, RowCounts) {
+        let timeline = self.timeline();
+        let segment_count = timeline.segment_count();
+
+        let mut active_orders = vec![Vec::new(); segment_count];
+        let mut starts_here = vec![false; segment_count];
+        for (i, span) in timeline.spans.iter().enumerate() {
+            if span.is_empty() {
+                continue;
+            }
+            starts_here[span.start] = true;
+            for k in span.clone() {
+                active_orders[k].push(i);
+            }
+        }
+
+        let is_binding = |orders: &[usize]| {
+            orders
+                .iter()
+                .map(|&i| self.orders[i].demand as u64)
+                .sum::<u64>()
+                > self.capacity as u64
+        };
+
+        let mut counts = RowCounts::default();
+        for (k, orders) in active_orders.iter().enumerate() {
+            if is_binding(orders) {
+                counts.per_period += timeline.segment_length(k);
+                counts.per_segment += 1;
+            }
+        }
+
+        // o conjunto no inicio k está contido no do proximo inicio se nenhuma
+        // das suas ordens termina antes dele
+        let start_segments: Vec<usize> = (0..segment_count).filter(|&k| starts_here[k]).collect();
+        let mut rows = Vec::new();
+        for (j, &k) in start_segments.iter().enumerate() {
+            let is_maximal = match start_segments.get(j + 1) {
+                Some(&next) => active_orders[k]
+                    .iter()
+                    .any(|&i| timeline.spans[i].end <= next),
+                None => true,
+            };
+
+            if is_maximal && is_binding(&active_orders[k]) {
+                rows.push(CapacityRow {
+                    period: timeline.segment_start(k),
+                    orders: active_orders[k].clone(),
+                });
+            }
+        }
+        counts.cliques = rows.len();
+
+        (rows, counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::{instance, random_instances};
+
+    #[test]
+    fn rows_and_counts_by_hand() {
+        // {0, 1} no periodo 3 está contido em {0, 1, 2} no periodo 4 e sai; {0} no
+        // periodo 1 e {4} no 15 cabem na capacidade
+        let instance = instance(
+            10,
+            &[
+                (1, 6, 1, 10),
+                (1, 6, 3, 5),
+                (1, 6, 4, 8),
+                (1, 5, 9, 12),
+                (1, 2, 15, 16),
+            ],
+        );
+        let (rows, counts) = instance.capacity_rows();
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|row| (row.period, row.orders.clone()))
+            .collect();
+        assert_eq!(rows, [(4, vec![0, 1, 2]), (9, vec![0, 3])]);
+        // periodos 3-10 estouram, em 4 segmentos: [3], [4, 5], [6, 8] e [9, 10]
+        assert_eq!(counts.per_period, 8);
+        assert_eq!(counts.per_segment, 4);
+        assert_eq!(counts.cliques, 2);
+    }
+
+    #[test]
+    fn rows_are_maximal_binding_cliques() {
+        for instance in random_instances(7, 300, false) {
+            let (rows, counts) = instance.capacity_rows();
+            let demand = |orders: &[usize]| -> u64 {
+                orders
+                    .iter()
+                    .map(|&i| instance.orders[i].demand as u64)
+                    .sum()
+            };
+            let active = |period: u32| -> Vec<usize> {
+                (0..instance.orders.len())
+                    .filter(|&i| {
+                        instance.orders[i].start <= period && period <= instance.orders[i].end
+                    })
+                    .collect()
+            };
+
+            for row in &rows {
+                // a linha é o conjunto ativo no seu periodo, estoura a capacidade e
+                // nenhuma outra ordem cruza todas as suas
+                assert_eq!(row.orders, active(row.period as u32), "{:?}", instance);
+                assert!(demand(&row.orders) > instance.capacity as u64);
+                let (start, end) = row.orders.iter().fold((0, u32::MAX), |(s, e), &i| {
+                    (
+                        s.max(instance.orders[i].start),
+                        e.min(instance.orders[i].end),
+                    )
+                });
+                assert!(instance
+                    .orders
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !row.orders.contains(i))
+                    .all(|(_, o)| o.start > end || o.end < start));
+            }
+            for (a, row) in rows.iter().enumerate() {
+                for other in &rows[a + 1..] {
+                    assert!(!row.orders.iter().all(|i| other.orders.contains(i)));
+                    assert!(!other.orders.iter().all(|i| row.orders.contains(i)));
+                }
+            }
+
+            // todo periodo que estoura é coberto por alguma linha
+            let mut binding_periods = 0;
+            for period in 0..=30 {
+                let orders = active(period);
+                if demand(&orders) > instance.capacity as u64 {
+                    binding_periods += 1;
+                    assert!(rows
+                        .iter()
+                        .any(|row| orders.iter().all(|i| row.orders.contains(i))));
+                }
+            }
+            assert_eq!(counts.per_period, binding_periods);
+            assert_eq!(counts.cliques, rows.len());
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use rayon::prelude::*;
+
+use crate::{parse::TkpInstance, tabu_search::Solution};
+
+// Subproblema independente: as ordens de uma componente não dividem nenhum segmento
+// em que a capacidade pode estourar com ordens de outra componente
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub instance: TkpInstance,
+    // indice original de cada ordem da componente, em ordem crescente
+    pub orders: Vec<usize>,
+}
+
+impl TkpInstance {
+    // Componentes conexas do grafo de sobreposição dos intervalos. Só contam as
+    // sobreposições em segmentos onde a demanda de todas as ordens juntas passa da
+    // capacidade: nos outros a restrição nunca é ativa, então não liga as ordens.
+    // Isso separa também instancias em que os intervalos se encadeiam de ponta a ponta
+    pub fn components(&self) -> Vec<Component> {
+        let timeline = self.timeline();
+        let total_demand = timeline.total_demand(self.orders.iter().map(|o| o.demand));
+
+        // union-find sobre as ordens, unindo cada ordem à primeira ordem vista em
+        // cada segmento que pode estourar
+        let mut parent: Vec<usize> = (0..self.orders.len()).collect();
+        let mut first_order = vec![None; timeline.segment_count()];
+        for (i, span) in timeline.spans.iter().enumerate() {
+            for k in span.clone() {
+                if total_demand[k] <= self.capacity as u64 {
+                    continue;
+                }
+                match first_order[k] {
+                    None => first_order[k] = Some(i),
+                    Some(j) => {
+                        let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                        parent[a] = b;
+                    }
+                }
+            }
+        }
+
+        let mut component_of = vec![usize::MAX; self.orders.len()];
+        let mut components: Vec<Vec<usize>> = Vec::new();
+        for i in 0..self.orders.len() {
+            let root = find(&mut parent, i);
+            if component_of[root] == usize::MAX {
+                component_of[root] = components.len();
+                components.push(Vec::new());
+            }
+            components[component_of[root]].push(i);
+        }
+
+        components
+            .into_iter()
+            .enumerate()
+            .map(|(c, orders)| {
+                let component_orders: Vec<_> = orders.iter().map(|&i| self.orders[i]).collect();
+                Component {
+                    instance: TkpInstance {
+                        name: format!("{}_c{}", self.name, c),
+                        order_count: component_orders.len(),
+                        capacity: self.capacity,
+                        orders: component_orders,
+                    },
+                    orders,
+                }
+            })
+            .collect()
+    }
+
+    // Resolve cada componente com `solve`, em paralelo, e junta as soluções parciais em
+    // uma solução da instancia inteira. Componentes de uma ordem só não chamam o solver
+    pub fn solve_components<F, E>(&self, solve: F) -> Result<Solution, E>
+    where
+        F: Fn(&TkpInstance) -> Result<Solution, E> + Sync,
+        E: Send,
+    {
+        let components = self.components();
+        let partial = components
+            .par_iter()
+            .map(|component| match component.instance.orders.as_slice() {
+                [order] => Ok(vec![order.demand <= self.capacity]),
+                _ => solve(&component.instance).map(|solution| solution.selected_orders),
+            })
+            .collect::<Result<Vec<_>, E>>()?;
+
+        let mut selected = vec![false; self.orders.len()];
+        for (component, component_selected) in components.iter().zip(partial) {
+            for (&i, value) in component.orders.iter().zip(component_selected) {
+                selected[i] = value;
+            }
+        }
+        Ok(Solution::from_selection(self, selected))
+    }
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dp::DEFAULT_MAX_STATES,
+        test_instances::{instance, optimum, random_instances},
+    };
+
+    #[test]
+    fn disjoint_clusters_are_separate_components() {
+        // ordens 0 e 2 disputam os periodos 1-5, ordens 1 e 3 os periodos 10-15; a ordem 4
+        // entra na primeira componente pelos periodos 3-4 e só encosta na segunda onde
+        // tudo cabe
+        let instance = instance(
+            10,
+            &[
+                (5, 6, 1, 5),
+                (4, 7, 10, 15),
+                (3, 6, 2, 4),
+                (6, 7, 12, 13),
+                (1, 1, 3, 11),
+            ],
+        );
+        let components = instance.components();
+        let orders: Vec<_> = components.iter().map(|c| c.orders.clone()).collect();
+        assert_eq!(orders, [vec![0, 2, 4], vec![1, 3]]);
+
+        for (c, component) in components.iter().enumerate() {
+            assert_eq!(component.instance.name, format!("test_c{}", c));
+            assert_eq!(component.instance.capacity, 10);
+            assert_eq!(component.instance.order_count, component.orders.len());
+            for (order, &i) in component.instance.orders.iter().zip(&component.orders) {
+                assert_eq!(*order, instance.orders[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn overlap_within_capacity_does_not_link() {
+        let instance = instance(10, &[(5, 4, 1, 5), (4, 4, 3, 8), (3, 2, 6, 9)]);
+        assert_eq!(instance.components().len(), 3);
+    }
+
+    #[test]
+    fn component_solutions_match_dp() {
+        for instance in random_instances(7, 200, false) {
+            let solution = instance
+                .solve_components(|component| {
+                    component
+                        .dp_solve(DEFAULT_MAX_STATES)
+                        .map(|result| result.solution)
+                })
+                .unwrap();
+            assert!(solution.is_feasible);
+            assert_eq!(solution.total_profit, optimum(&instance), "{:?}", instance);
+        }
+    }
+}
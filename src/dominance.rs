@@ -0,0 +1,107 @@
+use std::fmt;
+
+use crate::parse::{Order, TkpInstance};
+
+// Uma ordem j domina a ordem i quando o intervalo de j cabe dentro do de i, com demanda
+// menor ou igual e lucro maior ou igual: numa solução com i e sem j, trocar i por j
+// continua viavel e não perde lucro. Então existe solução otima com x_i <= x_j para todos
+// os pares ao mesmo tempo. Entre ordens iguais a de menor indice domina, para a relação
+// não ter ciclos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dominance {
+    pub name: String,
+    // para cada ordem, as ordens que a dominam
+    pub dominators: Vec<Vec<usize>>,
+}
+
+impl TkpInstance {
+    pub fn dominance(&self) -> Dominance {
+        // j só pode dominar i se começar dentro do intervalo de i
+        let mut by_start: Vec<usize> = (0..self.orders.len()).collect();
+        by_start.sort_by_key(|&i| self.orders[i].start);
+
+        let dominators = self
+            .orders
+            .iter()
+            .enumerate()
+            .map(|(i, order)| {
+                let first = by_start.partition_point(|&j| self.orders[j].start < order.start);
+                by_start[first..]
+                    .iter()
+                    .take_while(|&&j| self.orders[j].start <= order.end)
+                    .filter(|&&j| j != i && dominates((j, &self.orders[j]), (i, order)))
+                    .copied()
+                    .collect()
+            })
+            .collect();
+
+        Dominance {
+            name: self.name.clone(),
+            dominators,
+        }
+    }
+}
+
+fn dominates((j, a): (usize, &Order), (i, b): (usize, &Order)) -> bool {
+    a.start >= b.start
+        && a.end <= b.end
+        && a.demand <= b.demand
+        && a.profit >= b.profit
+        && (a != b || j < i)
+}
+
+impl Dominance {
+    pub fn pair_count(&self) -> usize {
+        self.dominators.iter().map(Vec::len).sum()
+    }
+
+    pub fn dominated_count(&self) -> usize {
+        self.dominators.iter().filter(|d| !d.is_empty()).count()
+    }
+
+    // ordens em algum par, dominadas ou dominantes
+    pub fn affected_count(&self) -> usize {
+        let mut affected: Vec<bool> = self.dominators.iter().map(|d| !d.is_empty()).collect();
+        for &j in self.dominators.iter().flatten() {
+            affected[j] = true;
+        }
+        affected.iter().filter(|a| **a).count()
+    }
+
+    // Pares (dominada, dominante) sem os implicados por transitividade: se k domina i e
+    // j domina k, x_i <= x_k <= x_j já garante x_i <= x_j
+    pub fn covering_pairs(&self) -> Vec<(usize, usize)> {
+        let mut implied = vec![false; self.dominators.len()];
+        let mut pairs = Vec::new();
+        for (i, dominators) in self.dominators.iter().enumerate() {
+            for &k in dominators {
+                for &j in &self.dominators[k] {
+                    implied[j] = true;
+                }
+            }
+            pairs.extend(dominators.iter().filter(|&&j| !implied[j]).map(|&j| (i, j)));
+            for &k in dominators {
+                for &j in &self.dominators[k] {
+                    implied[j] = false;
+                }
+            }
+        }
+        pairs
+    }
+}
+
+impl fmt::Display for Dominance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let share = |count: usize| 100.0 * count as f64 / self.dominators.len().max(1) as f64;
+        write!(
+            f,
+            "{}: {} dominance pairs, {} dominated orders ({:.1}%), {} orders affected ({:.1}%)",
+            self.name,
+            self.pair_count(),
+            self.dominated_count(),
+            share(self.dominated_count()),
+            self.affected_count(),
+            share(self.affected_count())
+        )
+    }
+}
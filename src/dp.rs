@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{parse::TkpInstance, tabu_search::Solution, validate::ValidationError};
+
+// Programação dinamica exata sobre o eixo do tempo: o estado é o conjunto de ordens
+// selecionadas ainda ativas. Em cada ponto as ordens que terminam saem do estado e as que
+// começam podem entrar se couberem; estados com o mesmo conjunto ficam só com o maior lucro.
+// Com capacidade pequena e pouca sobreposição os estados são poucos; o limite de estados
+// recusa as instancias em que isso não vale
+pub const DEFAULT_MAX_STATES: usize = 100_000;
+
+#[derive(Debug)]
+pub enum DpError {
+    InvalidInstance(ValidationError),
+    // o numero de estados passou do limite no ponto `period` do eixo
+    TooManyStates { limit: usize, period: u64 },
+}
+
+impl fmt::Display for DpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DpError::InvalidInstance(err) => write!(f, "{}", err),
+            DpError::TooManyStates { limit, period } => write!(
+                f,
+                "dynamic programming needs more than {} states at period {}",
+                limit, period
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DpError::InvalidInstance(err) => Some(err),
+            DpError::TooManyStates { .. } => None,
+        }
+    }
+}
+
+impl From<ValidationError> for DpError {
+    fn from(err: ValidationError) -> Self {
+        DpError::InvalidInstance(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DpResult {
+    pub solution: Solution,
+    // maior numero de estados em um ponto do eixo
+    pub peak_states: usize,
+    pub time: Duration,
+}
+
+#[derive(Clone, Copy)]
+struct State {
+    profit: u64,
+    load: u64,
+    // ultima decisão de seleção em `choices`, para reconstruir a solução
+    choice: Option<usize>,
+}
+
+impl TkpInstance {
+    pub fn dp_solve(&self, max_states: usize) -> Result<DpResult, DpError> {
+        self.check_valid()?;
+
+        let now = Instant::now();
+        let timeline = self.timeline();
+        let capacity = self.capacity as u64;
+
+        let mut starting = vec![Vec::new(); timeline.points.len()];
+        let mut ending = vec![Vec::new(); timeline.points.len()];
+        for (i, span) in timeline.spans.iter().enumerate() {
+            starting[span.start].push(i);
+            ending[span.end].push(i);
+        }
+
+        // decisões de seleção: (ordem, decisão anterior), uma lista encadeada por estado.
+        // Decisões de estados descartados ficam para trás, então a lista é compactada
+        // sempre que dobra em relação às decisões ainda alcançaveis
+        let mut choices: Vec<(usize, Option<usize>)> = Vec::new();
+        let mut live_choices = 0;
+        let mut states: HashMap<Vec<usize>, State> = HashMap::new();
+        states.insert(
+            Vec::new(),
+            State {
+                profit: 0,
+                load: 0,
+                choice: None,
+            },
+        );
+        let mut peak_states = 1;
+
+        for (point, &period) in timeline.points.iter().enumerate() {
+            if !ending[point].is_empty() {
+                let mut merged: HashMap<Vec<usize>, State> = HashMap::with_capacity(states.len());
+                for (mut active, mut state) in states {
+                    active.retain(|i| {
+                        let ends = timeline.spans[*i].end == point;
+                        if ends {
+                            state.load -= self.orders[*i].demand as u64;
+                        }
+                        !ends
+                    });
+                    keep_best(&mut merged, active, state);
+                }
+                states = merged;
+            }
+
+            for &i in &starting[point] {
+                let demand = self.orders[i].demand as u64;
+                let mut next: HashMap<Vec<usize>, State> = HashMap::with_capacity(states.len());
+                for (active, state) in states {
+                    if state.load + demand <= capacity {
+                        let mut with_order = active.clone();
+                        with_order.push(i);
+                        choices.push((i, state.choice));
+                        keep_best(
+                            &mut next,
+                            with_order,
+                            State {
+                                profit: state.profit + self.orders[i].profit as u64,
+                                load: state.load + demand,
+                                choice: Some(choices.len() - 1),
+                            },
+                        );
+                    }
+                    keep_best(&mut next, active, state);
+                }
+                states = next;
+
+                if choices.len() > 2 * live_choices.max(max_states) {
+                    compact(&mut choices, &mut states);
+                    live_choices = choices.len();
+                }
+                if states.len() > max_states {
+                    return Err(DpError::TooManyStates {
+                        limit: max_states,
+                        period,
+                    });
+                }
+                peak_states = peak_states.max(states.len());
+            }
+        }
+
+        let best = states
+            .values()
+            .max_by_key(|state| state.profit)
+            .and_then(|state| state.choice);
+        let mut selected = vec![false; self.orders.len()];
+        let mut choice = best;
+        while let Some(c) = choice {
+            let (order, previous) = choices[c];
+            selected[order] = true;
+            choice = previous;
+        }
+
+        Ok(DpResult {
+            solution: Solution::from_selection(self, selected),
+            peak_states,
+            time: now.elapsed(),
+        })
+    }
+}
+
+// Mantem só as decisões alcançaveis a partir de `states`, renumeradas na mesma ordem
+// (a decisão anterior sempre tem indice menor)
+fn compact(choices: &mut Vec<(usize, Option<usize>)>, states: &mut HashMap<Vec<usize>, State>) {
+    let mut reachable = vec![false; choices.len()];
+    let mut pending: Vec<usize> = states.values().filter_map(|state| state.choice).collect();
+    while let Some(c) = pending.pop() {
+        if !reachable[c] {
+            reachable[c] = true;
+            pending.extend(choices[c].1);
+        }
+    }
+
+    let mut new_index = vec![usize::MAX; choices.len()];
+    let mut compacted = Vec::new();
+    for (c, &(order, previous)) in choices.iter().enumerate() {
+        if reachable[c] {
+            new_index[c] = compacted.len();
+            compacted.push((order, previous.map(|p| new_index[p])));
+        }
+    }
+    for state in states.values_mut() {
+        state.choice = state.choice.map(|c| new_index[c]);
+    }
+    *choices = compacted;
+}
+
+fn keep_best(states: &mut HashMap<Vec<usize>, State>, active: Vec<usize>, state: State) {
+    states
+        .entry(active)
+        .and_modify(|current| {
+            if state.profit > current.profit {
+                *current = state;
+            }
+        })
+        .or_insert(state);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::{brute_force, optimum, random_instances};
+
+    use super::*;
+
+    #[test]
+    fn dp_matches_brute_force() {
+        for instance in random_instances(1, 300, false) {
+            assert_eq!(optimum(&instance), brute_force(&instance), "{:?}", instance);
+        }
+    }
+
+    #[test]
+    fn dp_compaction_keeps_solution() {
+        // limite pequeno força a compactação das decisões a cada poucas ordens
+        for instance in random_instances(2, 300, false) {
+            match instance.dp_solve(8) {
+                Ok(result) => assert_eq!(result.solution.total_profit, optimum(&instance)),
+                Err(DpError::TooManyStates { limit, .. }) => assert_eq!(limit, 8),
+                Err(err) => panic!("{}", err),
+            }
+        }
+    }
+}
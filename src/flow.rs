@@ -0,0 +1,230 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{parse::TkpInstance, tabu_search::Solution, validate::ValidationError};
+
+// Com a mesma demanda d em todas as ordens, a capacidade vira "no maximo k = C / d ordens
+// ativas ao mesmo tempo", e o TKP tem solução exata por fluxo de custo minimo no eixo do tempo:
+// k unidades de fluxo vão do primeiro ao ultimo ponto, seguindo o eixo (custo 0) ou
+// pulando pelo arco de uma ordem, do inicio até o fim + 1 (capacidade 1, custo -lucro).
+// Em cada periodo o fluxo total é k, então no maximo k ordens selecionadas o cobrem
+impl TkpInstance {
+    // Demanda comum a todas as ordens, se houver
+    pub fn uniform_demand(&self) -> Option<u32> {
+        let demand = self.orders.first()?.demand;
+        self.orders
+            .iter()
+            .all(|o| o.demand == demand)
+            .then_some(demand)
+    }
+
+    // Solução otima para instancias de demanda uniforme, `None` se as demandas variam
+    pub fn flow_solve(&self) -> Result<Option<Solution>, ValidationError> {
+        self.check_valid()?;
+
+        let Some(demand) = self.uniform_demand() else {
+            return Ok(None);
+        };
+
+        let timeline = self.timeline();
+        // mais unidades que o maximo de ordens sobrepostas não mudam a solução
+        let mut overlap_delta = vec![0i64; timeline.points.len() + 1];
+        for span in &timeline.spans {
+            overlap_delta[span.start] += 1;
+            overlap_delta[span.end] -= 1;
+        }
+        let max_overlap = overlap_delta
+            .iter()
+            .scan(0i64, |active, delta| {
+                *active += delta;
+                Some(*active)
+            })
+            .max()
+            .unwrap_or(0) as usize;
+        let k = match demand {
+            0 => max_overlap,
+            demand => ((self.capacity / demand) as usize).min(max_overlap),
+        };
+
+        let node_count = timeline.points.len();
+        let mut graph = FlowGraph::new(node_count);
+        for j in 1..node_count {
+            graph.add_edge(j - 1, j, k as i64, 0);
+        }
+        let order_edges: Vec<usize> = self
+            .orders
+            .iter()
+            .zip(&timeline.spans)
+            .map(|(order, span)| graph.add_edge(span.start, span.end, 1, -(order.profit as i64)))
+            .collect();
+
+        if node_count > 0 {
+            graph.min_cost_flow(0, node_count - 1, k as i64);
+        }
+
+        let selected = order_edges
+            .iter()
+            .map(|&e| graph.edges[e].capacity == 0)
+            .collect();
+        Ok(Some(Solution::from_selection(self, selected)))
+    }
+}
+
+struct Edge {
+    to: usize,
+    // capacidade residual; o arco reverso de `e` é `e ^ 1`
+    capacity: i64,
+    cost: i64,
+}
+
+struct FlowGraph {
+    edges: Vec<Edge>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl FlowGraph {
+    fn new(node_count: usize) -> Self {
+        Self {
+            edges: Vec::new(),
+            adjacency: vec![Vec::new(); node_count],
+        }
+    }
+
+    // Arcos sempre vão de um ponto para um ponto posterior do eixo
+    fn add_edge(&mut self, from: usize, to: usize, capacity: i64, cost: i64) -> usize {
+        let index = self.edges.len();
+        self.edges.push(Edge { to, capacity, cost });
+        self.edges.push(Edge {
+            to: from,
+            capacity: 0,
+            cost: -cost,
+        });
+        self.adjacency[from].push(index);
+        self.adjacency[to].push(index + 1);
+        index
+    }
+
+    // Caminhos minimos sucessivos com potenciais. O grafo é aciclico e ordenado pelos
+    // pontos, então os potenciais iniciais saem de uma passada em ordem topologica
+    // apesar dos custos negativos. Para ao enviar `flow` unidades ou quando o caminho
+    // mais barato não melhora mais o custo
+    fn min_cost_flow(&mut self, source: usize, sink: usize, flow: i64) {
+        let node_count = self.adjacency.len();
+        let mut potential = vec![i64::MAX; node_count];
+        potential[source] = 0;
+        for u in 0..node_count {
+            if potential[u] == i64::MAX {
+                continue;
+            }
+            for &e in &self.adjacency[u] {
+                let edge = &self.edges[e];
+                if edge.capacity > 0 && potential[u] + edge.cost < potential[edge.to] {
+                    potential[edge.to] = potential[u] + edge.cost;
+                }
+            }
+        }
+
+        let mut remaining = flow;
+        while remaining > 0 {
+            // Dijkstra com custos reduzidos, que são não negativos
+            let mut distance = vec![i64::MAX; node_count];
+            let mut parent_edge = vec![usize::MAX; node_count];
+            let mut heap = BinaryHeap::new();
+            distance[source] = 0;
+            heap.push(Reverse((0, source)));
+            while let Some(Reverse((d, u))) = heap.pop() {
+                if d > distance[u] {
+                    continue;
+                }
+                for &e in &self.adjacency[u] {
+                    let edge = &self.edges[e];
+                    if edge.capacity == 0 || potential[edge.to] == i64::MAX {
+                        continue;
+                    }
+                    let next = d + edge.cost + potential[u] - potential[edge.to];
+                    if next < distance[edge.to] {
+                        distance[edge.to] = next;
+                        parent_edge[edge.to] = e;
+                        heap.push(Reverse((next, edge.to)));
+                    }
+                }
+            }
+
+            if distance[sink] == i64::MAX {
+                break;
+            }
+            for u in 0..node_count {
+                if distance[u] != i64::MAX {
+                    potential[u] += distance[u];
+                }
+            }
+            // custo real do caminho; sem custo negativo as unidades restantes só seguem o eixo
+            if potential[sink] - potential[source] >= 0 {
+                break;
+            }
+
+            let mut bottleneck = remaining;
+            let mut v = sink;
+            while v != source {
+                let e = parent_edge[v];
+                bottleneck = bottleneck.min(self.edges[e].capacity);
+                v = self.edges[e ^ 1].to;
+            }
+            let mut v = sink;
+            while v != source {
+                let e = parent_edge[v];
+                self.edges[e].capacity -= bottleneck;
+                self.edges[e ^ 1].capacity += bottleneck;
+                v = self.edges[e ^ 1].to;
+            }
+            remaining -= bottleneck;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::{brute_force, instance, random_instances};
+
+    #[test]
+    fn flow_matches_brute_force_on_uniform_demand() {
+        for instance in random_instances(4, 300, true) {
+            let solution = instance.flow_solve().unwrap().unwrap();
+            assert!(solution.is_feasible);
+            assert_eq!(
+                solution.total_profit,
+                brute_force(&instance),
+                "{:?}",
+                instance
+            );
+        }
+    }
+
+    #[test]
+    fn non_uniform_demand_returns_none() {
+        let instance = instance(10, &[(5, 3, 1, 4), (4, 4, 2, 6)]);
+        assert_eq!(instance.uniform_demand(), None);
+        assert_eq!(instance.flow_solve().unwrap(), None);
+    }
+
+    #[test]
+    fn zero_capacity_selects_nothing() {
+        let blocked = instance(0, &[(5, 3, 1, 4), (4, 3, 2, 6), (7, 3, 8, 9)]);
+        let solution = blocked.flow_solve().unwrap().unwrap();
+        assert!(solution.is_feasible);
+        assert_eq!(solution.total_profit, 0);
+        assert!(solution.selected_orders.iter().all(|s| !s));
+
+        // demanda zero sempre cabe, mesmo sem capacidade
+        let free = instance(0, &[(5, 0, 1, 4), (4, 0, 2, 6)]);
+        let solution = free.flow_solve().unwrap().unwrap();
+        assert_eq!(solution.total_profit, 9);
+    }
+
+    #[test]
+    fn orders_above_capacity_are_never_selected() {
+        let instance = instance(5, &[(5, 6, 1, 4), (4, 6, 2, 6), (7, 6, 8, 9)]);
+        let solution = instance.flow_solve().unwrap().unwrap();
+        assert!(solution.is_feasible);
+        assert_eq!(solution.total_profit, 0);
+    }
+}
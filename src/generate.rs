@@ -0,0 +1,378 @@
+use std::fmt;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::parse::{Order, TkpInstance};
+
+// Distribuição de um valor inteiro (lucro ou demanda)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueDistribution {
+    Uniform { min: u32, max: u32 },
+}
+
+// O lucro pode ser sorteado independente da demanda ou derivado dela
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfitDistribution {
+    Independent(ValueDistribution),
+    // lucro = demanda + offset + ruido uniforme em [-noise, noise], no minimo 1
+    CorrelatedWithDemand { offset: i64, noise: u32 },
+}
+
+// Duração de uma ordem (fim - inicio), sempre dentro de [min, max]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DurationDistribution {
+    Uniform { min: u32, max: u32 },
+    // exponencial arredondada com a média dada
+    Exponential { mean: f64, min: u32, max: u32 },
+    // pareto truncada com escala `min` e forma `alpha`: cauda pesada
+    HeavyTailed { alpha: f64, min: u32, max: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorParams {
+    pub order_count: usize,
+    pub capacity: u32,
+    // ultimo periodo em que uma ordem pode terminar (periodos começam em 1)
+    pub horizon: u32,
+    pub profit: ProfitDistribution,
+    pub demand: ValueDistribution,
+    pub duration: DurationDistribution,
+    // as instancias I e U vem ordenadas por inicio, a HB não
+    pub sort_by_start: bool,
+}
+
+// Parametros que o gerador recusa em vez de entrar em panico ao sortear
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratorError {
+    // intervalo vazio em uma distribuição: `min` > `max`
+    EmptyRange {
+        field: &'static str,
+        min: u32,
+        max: u32,
+    },
+    // média ou forma de uma distribuição precisa ser finita e positiva
+    InvalidShape {
+        field: &'static str,
+        value: f64,
+    },
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratorError::EmptyRange { field, min, max } => {
+                write!(f, "{}: min {} is greater than max {}", field, min, max)
+            }
+            GeneratorError::InvalidShape { field, value } => {
+                write!(
+                    f,
+                    "{}: expected a finite positive value, found {}",
+                    field, value
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {}
+
+// Familias de instancias em tkp_instances/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    I5,
+    I25,
+    I72,
+    I90,
+    I100,
+    U2,
+    U100,
+    HB,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 8] = [
+        Preset::I5,
+        Preset::I25,
+        Preset::I72,
+        Preset::I90,
+        Preset::I100,
+        Preset::U2,
+        Preset::U100,
+        Preset::HB,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::I5 => "I5",
+            Preset::I25 => "I25",
+            Preset::I72 => "I72",
+            Preset::I90 => "I90",
+            Preset::I100 => "I100",
+            Preset::U2 => "U2",
+            Preset::U100 => "U100",
+            Preset::HB => "HB",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Preset::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    // Parametros que aproximam a instancia original, com o mesmo numero de ordens
+    pub fn params(&self) -> GeneratorParams {
+        // familia I: lucro e demanda uniformes em [10, 100], durações ~ exponenciais
+        let family_i = |order_count, horizon, mean, max| GeneratorParams {
+            order_count,
+            capacity: 100,
+            horizon,
+            profit: ProfitDistribution::Independent(ValueDistribution::Uniform {
+                min: 10,
+                max: 100,
+            }),
+            demand: ValueDistribution::Uniform { min: 10, max: 100 },
+            duration: DurationDistribution::Exponential { mean, min: 1, max },
+            sort_by_start: true,
+        };
+        // familia U: tudo uniforme em [1, 100]
+        let family_u = |capacity, horizon, max| GeneratorParams {
+            order_count: 1000,
+            capacity,
+            horizon,
+            profit: ProfitDistribution::Independent(ValueDistribution::Uniform {
+                min: 1,
+                max: 100,
+            }),
+            demand: ValueDistribution::Uniform { min: 1, max: 100 },
+            duration: DurationDistribution::Uniform { min: 1, max },
+            sort_by_start: true,
+        };
+
+        match self {
+            Preset::I5 => family_i(3209, 3201, 10.0, 82),
+            Preset::I25 => family_i(5875, 3201, 10.9, 93),
+            Preset::I72 => family_i(3424, 897, 7.8, 51),
+            Preset::I90 => family_i(13025, 1921, 4.4, 36),
+            Preset::I100 => family_i(7709, 1921, 8.7, 64),
+            Preset::U2 => family_u(500, 1010, 10),
+            Preset::U100 => family_u(501, 1100, 100),
+            // demanda = lucro + 100
+            Preset::HB => GeneratorParams {
+                order_count: 10000,
+                capacity: 1000,
+                horizon: 1100,
+                profit: ProfitDistribution::CorrelatedWithDemand {
+                    offset: -100,
+                    noise: 0,
+                },
+                demand: ValueDistribution::Uniform {
+                    min: 101,
+                    max: 1000,
+                },
+                duration: DurationDistribution::Uniform { min: 10, max: 100 },
+                sort_by_start: false,
+            },
+        }
+    }
+
+    // Mesma familia com `order_count` ordens, o horizonte cresce junto
+    // para manter a densidade de ordens por periodo
+    pub fn scaled(&self, order_count: usize) -> GeneratorParams {
+        let base = self.params();
+        let horizon = (base.horizon as f64 * order_count as f64 / base.order_count as f64).round();
+        let max_duration = match base.duration {
+            DurationDistribution::Uniform { max, .. }
+            | DurationDistribution::Exponential { max, .. }
+            | DurationDistribution::HeavyTailed { max, .. } => max,
+        };
+
+        GeneratorParams {
+            order_count,
+            horizon: (horizon as u32).max(max_duration + 1),
+            ..base
+        }
+    }
+}
+
+impl ValueDistribution {
+    fn validate(&self, field: &'static str) -> Result<(), GeneratorError> {
+        match *self {
+            ValueDistribution::Uniform { min, max } => check_range(field, min, max),
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> u32 {
+        match *self {
+            ValueDistribution::Uniform { min, max } => rng.gen_range(min..=max),
+        }
+    }
+}
+
+impl DurationDistribution {
+    fn validate(&self) -> Result<(), GeneratorError> {
+        let (min, max, shape) = match *self {
+            DurationDistribution::Uniform { min, max } => (min, max, None),
+            DurationDistribution::Exponential { mean, min, max } => (min, max, Some(mean)),
+            DurationDistribution::HeavyTailed { alpha, min, max } => (min, max, Some(alpha)),
+        };
+        check_range("duration", min, max)?;
+        match shape {
+            Some(value) if !(value.is_finite() && value > 0.0) => {
+                Err(GeneratorError::InvalidShape {
+                    field: "duration",
+                    value,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> u32 {
+        match *self {
+            DurationDistribution::Uniform { min, max } => rng.gen_range(min..=max),
+            DurationDistribution::Exponential { mean, min, max } => {
+                let u: f64 = rng.gen();
+                let x = -mean * (1.0 - u).ln();
+                (x.round() as u32).clamp(min, max)
+            }
+            DurationDistribution::HeavyTailed { alpha, min, max } => {
+                // inversa da CDF da pareto truncada em [min, max]
+                let (low, high) = (min.max(1) as f64, max.max(1) as f64);
+                let u: f64 = rng.gen();
+                let tail = 1.0 - (low / high).powf(alpha);
+                let x = low / (1.0 - u * tail).powf(1.0 / alpha);
+                (x.floor() as u32).clamp(min, max)
+            }
+        }
+    }
+}
+
+fn check_range(field: &'static str, min: u32, max: u32) -> Result<(), GeneratorError> {
+    if min > max {
+        return Err(GeneratorError::EmptyRange { field, min, max });
+    }
+    Ok(())
+}
+
+impl GeneratorParams {
+    pub fn validate(&self) -> Result<(), GeneratorError> {
+        self.demand.validate("demand")?;
+        if let ProfitDistribution::Independent(distribution) = self.profit {
+            distribution.validate("profit")?;
+        }
+        self.duration.validate()
+    }
+
+    // Gera uma instancia; os mesmos (params, seed) sempre geram a mesma instancia
+    pub fn generate(&self, name: &str, seed: u64) -> Result<TkpInstance, GeneratorError> {
+        self.validate()?;
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let horizon = self.horizon.max(1);
+
+        let mut orders: Vec<Order> = (0..self.order_count)
+            .map(|_| {
+                let demand = self.demand.sample(&mut rng);
+                let profit = match self.profit {
+                    ProfitDistribution::Independent(distribution) => distribution.sample(&mut rng),
+                    ProfitDistribution::CorrelatedWithDemand { offset, noise } => {
+                        let noise = noise as i64;
+                        let noise = rng.gen_range(-noise..=noise);
+                        (demand as i64 + offset + noise).max(1) as u32
+                    }
+                };
+
+                // a ordem inteira precisa caber em [1, horizon]
+                let duration = self.duration.sample(&mut rng).min(horizon - 1);
+                let start = rng.gen_range(1..=horizon - duration);
+
+                Order {
+                    profit,
+                    demand,
+                    start,
+                    end: start + duration,
+                }
+            })
+            .collect();
+
+        if self.sort_by_start {
+            orders.sort_by_key(|o| (o.start, o.end));
+        }
+
+        Ok(TkpInstance {
+            order_count: orders.len(),
+            capacity: self.capacity,
+            orders,
+            name: name.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_generates_same_instance() {
+        for preset in Preset::ALL {
+            let params = preset.scaled(200);
+            let a = params.generate(preset.name(), 7).unwrap();
+            let b = params.generate(preset.name(), 7).unwrap();
+            assert_eq!(a, b, "{}", preset.name());
+
+            let c = params.generate(preset.name(), 8).unwrap();
+            assert_ne!(a.orders, c.orders, "{}", preset.name());
+        }
+    }
+
+    #[test]
+    fn orders_fit_in_horizon() {
+        for preset in Preset::ALL {
+            let params = preset.scaled(500);
+            let instance = params.generate(preset.name(), 1).unwrap();
+            assert_eq!(instance.order_count, 500);
+            for order in &instance.orders {
+                assert!(1 <= order.start && order.start <= order.end);
+                assert!(order.end <= params.horizon);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_empty_ranges() {
+        let mut params = Preset::U2.scaled(10);
+        params.demand = ValueDistribution::Uniform { min: 5, max: 4 };
+        assert_eq!(
+            params.generate("bad", 0),
+            Err(GeneratorError::EmptyRange {
+                field: "demand",
+                min: 5,
+                max: 4
+            })
+        );
+
+        let mut params = Preset::U2.scaled(10);
+        params.profit =
+            ProfitDistribution::Independent(ValueDistribution::Uniform { min: 2, max: 1 });
+        assert!(matches!(
+            params.generate("bad", 0),
+            Err(GeneratorError::EmptyRange {
+                field: "profit",
+                ..
+            })
+        ));
+
+        let mut params = Preset::I5.scaled(10);
+        params.duration = DurationDistribution::Exponential {
+            mean: 0.0,
+            min: 1,
+            max: 10,
+        };
+        assert!(matches!(
+            params.generate("bad", 0),
+            Err(GeneratorError::InvalidShape {
+                field: "duration",
+                ..
+            })
+        ));
+    }
+}
@@ -1,88 +1,487 @@
-use highs::{HighsModelStatus, RowProblem, Sense};
+use std::{
+    fmt,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use crate::parse::TkpInstance;
+use highs::HighsModelStatus;
 
-impl TkpInstance {
-    pub fn higgs_solve(&self) -> i32 {
-        let mut pb = RowProblem::default();
+use crate::{
+    highs_ffi::{Highs, HighsCallError, OptionValue},
+    parse::TkpInstance,
+    solution_file::SolutionOutput,
+    tabu_search::Solution,
+    validate::ValidationError,
+};
 
-        // X representa uma variavel binaria representando se uma ordem é escolhida ou não
-        // com seu lucro como coeficiente
-        let x: Vec<_> = self
-            .orders
-            .iter()
-            .map(|o| pb.add_integer_column(o.profit as f64, 0..=1))
-            .collect();
+// Algoritmo da relaxação linear em `lp_bound`. O MIP de `higgs_solve` sempre usa a
+// escolha do HiGHS: com `solver` = simplex ou ipm num MIP o HiGHS resolve só a
+// relaxação linear e devolve o ponto fracionario como otimo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LpSolver {
+    // escolha do HiGHS
+    Choose,
+    Simplex,
+    Ipm,
+}
 
-        // Tempo do fim da ordem mais tardia (representa o T final do problema)
-        let final_time = self.orders.iter().map(|o| o.end).max().unwrap_or(0);
+impl LpSolver {
+    pub const ALL: [LpSolver; 3] = [LpSolver::Choose, LpSolver::Simplex, LpSolver::Ipm];
 
-        // Para cada segundo do intervalo total
-        // a soma das demandas das ordens selecionadas devem ser menor ou igual a capacidade
-        for t in 0..=final_time {
-            // ordens que estao ativas em t, zipadas com suas respectivas variaveis de seleção
-            let interval_orders: Vec<_> = self
-                .orders
-                .iter()
-                .zip(x.iter())
-                .filter(|(order, _)| order.start <= t && order.end >= t)
-                .map(|(order, x)| (*x, order.demand as f64))
-                .collect();
+    pub fn name(&self) -> &'static str {
+        match self {
+            LpSolver::Choose => "choose",
+            LpSolver::Simplex => "simplex",
+            LpSolver::Ipm => "ipm",
+        }
+    }
 
-            // se a some das demandas das ordens ativas em t for menor ou igual a capacidade
-            // não precisamos de restrição (todas as variaveis podem estar ligadas)
-            let sum_demands = interval_orders
-                .iter()
-                .map(|(_, demand)| demand)
-                .sum::<f64>();
+    pub fn from_name(name: &str) -> Option<Self> {
+        LpSolver::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+// Para onde vai o log do HiGHS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MipLog {
+    Off,
+    Stdout,
+    File(PathBuf),
+}
+
+// Opções de uma execução do HiGHS. `None` deixa o valor padrão do HiGHS
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipOptions {
+    pub time_limit: Option<Duration>,
+    pub relative_gap: Option<f64>,
+    pub absolute_gap: Option<f64>,
+    pub threads: Option<u32>,
+    // o HiGHS aceita sementes de 0 a i32::MAX, os bits acima são descartados
+    pub random_seed: Option<u32>,
+    // só vale para `lp_bound`
+    pub lp_solver: LpSolver,
+    pub log: MipLog,
+    // adiciona as restrições de dominancia entre ordens (ver `dominance`)
+    pub dominance: bool,
+}
+
+impl Default for MipOptions {
+    fn default() -> Self {
+        Self {
+            time_limit: None,
+            relative_gap: None,
+            absolute_gap: None,
+            threads: None,
+            random_seed: None,
+            lp_solver: LpSolver::Choose,
+            log: MipLog::Off,
+            dominance: false,
+        }
+    }
+}
+
+impl MipOptions {
+    // Opções do HiGHS na ordem em que são aplicadas, para o MIP ou para a sua
+    // relaxação linear (`relaxed`)
+    fn highs_options(&self, relaxed: bool) -> Vec<(&'static str, OptionValue)> {
+        let solver = if relaxed {
+            self.lp_solver
+        } else {
+            LpSolver::Choose
+        };
+        let mut options = vec![
+            ("presolve", OptionValue::Str("on".into())),
+            ("solver", OptionValue::Str(solver.name().into())),
+            ("parallel", OptionValue::Str("on".into())),
+        ];
+        // o MIP roda o branch and bound de verdade, não só a relaxação
+        if !relaxed {
+            options.push(("solve_relaxation", OptionValue::Bool(false)));
+        }
+
+        if let Some(time_limit) = self.time_limit {
+            options.push(("time_limit", OptionValue::Double(time_limit.as_secs_f64())));
+        }
+        if let Some(gap) = self.relative_gap {
+            options.push(("mip_rel_gap", OptionValue::Double(gap)));
+        }
+        if let Some(gap) = self.absolute_gap {
+            options.push(("mip_abs_gap", OptionValue::Double(gap)));
+        }
+        if let Some(threads) = self.threads {
+            options.push(("threads", OptionValue::Int(threads as i32)));
+        }
+        if let Some(seed) = self.random_seed {
+            options.push((
+                "random_seed",
+                OptionValue::Int((seed & i32::MAX as u32) as i32),
+            ));
+        }
+        options
+    }
+
+    // Opções de log, que não fazem parte dos parametros da solução
+    fn log_options(&self) -> Vec<(&'static str, OptionValue)> {
+        match &self.log {
+            MipLog::Off => vec![("output_flag", OptionValue::Bool(false))],
+            MipLog::Stdout => vec![
+                ("output_flag", OptionValue::Bool(true)),
+                ("log_to_console", OptionValue::Bool(true)),
+            ],
+            MipLog::File(path) => vec![
+                ("output_flag", OptionValue::Bool(true)),
+                ("log_to_console", OptionValue::Bool(false)),
+                (
+                    "log_file",
+                    OptionValue::Str(path.to_string_lossy().into_owned()),
+                ),
+            ],
+        }
+    }
+}
+
+// Resultado de uma execução do HiGHS. Limites de tempo e outros status não
+// otimos também chegam aqui, com o que o solver conseguiu até parar
+#[derive(Debug, Clone)]
+pub struct MipResult {
+    pub status: HighsModelStatus,
+    // lucro da melhor solução viavel, recalculado na instancia, se houver
+    pub objective: Option<f64>,
+    // limite superior provado pelo branch and bound
+    pub dual_bound: Option<f64>,
+    // gap relativo entre objective e dual_bound
+    pub gap: Option<f64>,
+    pub time: Duration,
+    // nós explorados pelo branch and bound
+    pub nodes: Option<u64>,
+    pub rows: usize,
+    pub columns: usize,
+    pub solution: Option<Solution>,
+}
+
+impl MipResult {
+    pub fn is_optimal(&self) -> bool {
+        self.status == HighsModelStatus::Optimal
+    }
+}
+
+#[derive(Debug)]
+pub enum MipError {
+    InvalidInstance(ValidationError),
+    // a solução inicial tem um numero de ordens diferente da instancia
+    InitialSolutionLength { expected: usize, found: usize },
+    // a relaxação linear não terminou otima, então não ha limite valido
+    NoBound { status: HighsModelStatus },
+    // uma chamada da API do HiGHS retornou erro
+    Highs { call: &'static str },
+    // o HiGHS não informou os nós do branch and bound da execução `run` (cold ou warm)
+    NoNodeCount { run: &'static str },
+}
 
-            if interval_orders.is_empty() || self.capacity as f64 >= sum_demands {
-                continue;
+impl fmt::Display for MipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MipError::InvalidInstance(err) => write!(f, "{}", err),
+            MipError::InitialSolutionLength { expected, found } => write!(
+                f,
+                "initial solution has {} orders but the instance has {}",
+                found, expected
+            ),
+            MipError::NoBound { status } => {
+                write!(f, "LP relaxation stopped with status {:?}", status)
             }
+            MipError::Highs { call } => write!(f, "HiGHS error in {}", call),
+            MipError::NoNodeCount { run } => write!(
+                f,
+                "HiGHS reported no branch-and-bound node count for the {} run",
+                run
+            ),
+        }
+    }
+}
 
-            // o bound maximo é a capacidade de c
-            pb.add_row(0f64..=self.capacity as f64, interval_orders)
+impl std::error::Error for MipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MipError::InvalidInstance(err) => Some(err),
+            MipError::InitialSolutionLength { .. }
+            | MipError::NoBound { .. }
+            | MipError::Highs { .. }
+            | MipError::NoNodeCount { .. } => None,
         }
+    }
+}
 
+impl From<ValidationError> for MipError {
+    fn from(err: ValidationError) -> Self {
+        MipError::InvalidInstance(err)
+    }
+}
+
+impl From<HighsCallError> for MipError {
+    fn from(HighsCallError(call): HighsCallError) -> Self {
+        MipError::Highs { call }
+    }
+}
+
+impl TkpInstance {
+    pub fn higgs_solve(
+        &self,
+        options: &MipOptions,
+        initial: Option<&Solution>,
+        output: &SolutionOutput,
+    ) -> Result<MipResult, MipError> {
+        self.check_valid()?;
+
+        // o modelo só tem as ordens que o presolve não fixou, o lucro das fixadas
+        // entra como constante no objetivo
+        let (model, presolved) = self.presolved_mip_model(options.dominance);
+        println!("{}", presolved);
+        if presolved.is_solved() {
+            let solution = presolved.expand(self, &[]);
+            output.write(self, &solution, "presolve", None, Vec::new());
+            return Ok(MipResult {
+                status: HighsModelStatus::Optimal,
+                objective: Some(solution.total_profit as f64),
+                dual_bound: Some(solution.total_profit as f64),
+                gap: Some(0.0),
+                time: Duration::ZERO,
+                nodes: Some(0),
+                rows: 0,
+                columns: 0,
+                solution: Some(solution),
+            });
+        }
+
+        // X representa uma variavel binaria representando se uma ordem é escolhida ou não
+        // com seu lucro como coeficiente, e ha uma restrição de capacidade por clique maximal
+        // do grafo de intervalos
+        let counts = model.row_counts;
+        println!(
+            "capacity rows: {} per period, {} per segment, {} maximal cliques",
+            counts.per_period, counts.per_segment, counts.cliques
+        );
+        if options.dominance {
+            println!("dominance rows: {}", model.rows.len() - counts.cliques);
+        }
         println!(
             "solving {} constrants, {} variables",
-            pb.num_rows(),
-            pb.num_cols()
+            model.rows.len(),
+            model.columns.len()
         );
 
-        let mut model = pb.optimise(Sense::Maximise);
+        let mut highs = Highs::new();
+        highs.pass_model(&model, false)?;
 
-        // usar o solver simplex é significativamente mais rapido
-        model.set_option("presolve", "on");
+        let parameters = options.highs_options(false);
+        for (name, value) in parameters.iter().chain(&options.log_options()) {
+            highs.set_option(name, value)?;
+        }
 
-        model.set_option("solver", "simplex");
-        model.set_option("parallel", "on");
+        // uma solução inicial viavel vira o incumbente do branch and bound;
+        // o HiGHS descarta soluções inviaveis
+        if let Some(initial) = initial {
+            if initial.selected_orders.len() != self.orders.len() {
+                return Err(MipError::InitialSolutionLength {
+                    expected: self.orders.len(),
+                    found: initial.selected_orders.len(),
+                });
+            }
+            if initial.is_feasible {
+                let values: Vec<f64> = presolved
+                    .reduce_selection(&initial.selected_orders)
+                    .iter()
+                    .map(|&selected| selected as u8 as f64)
+                    .collect();
+                highs.set_solution(&values)?;
+            } else {
+                eprintln!("{}: initial solution is infeasible, ignoring it", self.name);
+            }
+        }
 
-        let solved = model.solve();
+        let now = Instant::now();
+        highs.run()?;
+        let time = now.elapsed();
 
-        assert_eq!(solved.status(), HighsModelStatus::Optimal);
-        let solution = solved.get_solution();
+        // a seleção arredondada é conferida na instancia original, e uma seleção inviavel
+        // não é devolvida nem escrita
+        let solution = highs
+            .has_feasible_solution()
+            .then(|| {
+                let selected: Vec<bool> =
+                    highs.column_values().iter().map(|&val| val > 0.5).collect();
+                presolved.expand(self, &selected)
+            })
+            .filter(|solution| {
+                if !solution.is_feasible {
+                    eprintln!(
+                        "{}: HiGHS solution violates capacity after rounding, discarding it",
+                        self.name
+                    );
+                }
+                solution.is_feasible
+            });
 
-        // escreve arquivo com a solução (para debug)
-        std::fs::write(
-            format!("{}.sol", self.name),
-            solution
-                .columns()
+        // escreve arquivo com a solução e os parametros usados
+        if let Some(solution) = &solution {
+            let parameters = parameters
                 .iter()
-                .map(|&val| (val.round() as i32).to_string())
-                .collect::<Vec<_>>()
-                .join("\n"),
-        )
-        .unwrap();
-
-        // calcula o valor da solução, somand o lucro das ordens selecionadas
-        let objective_value: i32 = solution
-            .columns()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            output.write(self, solution, "highs", None, parameters);
+        }
+
+        Ok(MipResult {
+            status: highs.model_status(),
+            objective: solution.as_ref().map(|s| s.total_profit as f64),
+            dual_bound: highs.double_info("mip_dual_bound"),
+            gap: highs.double_info("mip_gap"),
+            time,
+            // o HiGHS informa -1 quando não rodou o branch and bound
+            nodes: highs
+                .int64_info("mip_node_count")
+                .and_then(|nodes| u64::try_from(nodes).ok()),
+            rows: model.rows.len(),
+            columns: model.columns.len(),
+            solution,
+        })
+    }
+}
+
+impl TkpInstance {
+    // `higgs_solve` em cada componente independente (ver `components`), em paralelo.
+    // Uma componente sem solução viavel (ex: limite de tempo) fica sem ordens selecionadas
+    pub fn higgs_solve_components(
+        &self,
+        options: &MipOptions,
+        output: &SolutionOutput,
+    ) -> Result<Solution, MipError> {
+        self.check_valid()?;
+
+        let solution = self.solve_components(|component| {
+            let result = component.higgs_solve(options, None, &SolutionOutput::Disabled)?;
+            if !result.is_optimal() {
+                eprintln!("{}: {:?}", component.name, result.status);
+            }
+            Ok::<_, MipError>(result.solution.unwrap_or_else(|| {
+                Solution::from_selection(component, vec![false; component.orders.len()])
+            }))
+        })?;
+
+        let parameters = options
+            .highs_options(false)
             .iter()
-            .enumerate()
-            .map(|(i, &val)| (val.round() as i32) * self.orders[i].profit as i32)
-            .sum();
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        output.write(self, &solution, "highs-components", None, parameters);
+        Ok(solution)
+    }
 
-        return objective_value;
+    // Limite superior pela relaxação linear do modelo de `higgs_solve`, com as
+    // desigualdades de clique opcionais. O lucro otimo é no maximo o piso desse valor
+    pub fn lp_bound(&self, options: &MipOptions, clique_cuts: bool) -> Result<f64, MipError> {
+        self.check_valid()?;
+
+        let (mut model, presolved) = self.presolved_mip_model(options.dominance);
+        if presolved.is_solved() {
+            return Ok(presolved.fixed_profit as f64);
+        }
+        if clique_cuts {
+            let cuts = model.add_clique_cuts();
+            println!("{}: {} clique cuts", self.name, cuts);
+        }
+
+        let mut highs = Highs::new();
+        highs.pass_model(&model, true)?;
+        for (name, value) in options
+            .highs_options(true)
+            .iter()
+            .chain(&options.log_options())
+        {
+            highs.set_option(name, value)?;
+        }
+        highs.run()?;
+
+        match highs.model_status() {
+            HighsModelStatus::Optimal => Ok(highs.objective_value()),
+            // sem linhas e colunas o limite é 0
+            HighsModelStatus::ModelEmpty => Ok(0.0),
+            status => Err(MipError::NoBound { status }),
+        }
+    }
+}
+
+// Comparação entre resolver do zero e partindo de uma solução heuristica
+#[derive(Debug, Clone)]
+pub struct WarmStartReport {
+    pub initial_profit: u32,
+    pub cold: MipResult,
+    pub warm: MipResult,
+}
+
+impl WarmStartReport {
+    // fração do tempo de resolução economizada pelo warm start (negativa se piorou)
+    pub fn time_reduction(&self) -> f64 {
+        let cold = self.cold.time.as_secs_f64();
+        if cold == 0.0 {
+            return 0.0;
+        }
+        (cold - self.warm.time.as_secs_f64()) / cold
+    }
+
+    // fração dos nós economizada pelo warm start, se o HiGHS informou as duas contagens
+    pub fn node_reduction(&self) -> Option<f64> {
+        match (self.cold.nodes, self.warm.nodes) {
+            (Some(cold), Some(warm)) if cold > 0 => Some((cold as f64 - warm as f64) / cold as f64),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WarmStartReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes = |result: &MipResult| result.nodes.map_or("-".into(), |n| n.to_string());
+        writeln!(f, "initial profit {}", self.initial_profit)?;
+        for (name, result) in [("cold", &self.cold), ("warm", &self.warm)] {
+            writeln!(
+                f,
+                "{}: {:?}, objective {}, {}ms, {} nodes",
+                name,
+                result.status,
+                result.objective.map_or("-".into(), |o| o.to_string()),
+                result.time.as_millis(),
+                nodes(result)
+            )?;
+        }
+        write!(f, "time reduction {:.1}%", self.time_reduction() * 100.0)?;
+        match self.node_reduction() {
+            Some(reduction) => writeln!(f, ", node reduction {:.1}%", reduction * 100.0),
+            None => writeln!(f),
+        }
+    }
+}
+
+impl TkpInstance {
+    // Resolve duas vezes com as mesmas opções, do zero e a partir de `initial`,
+    // para medir o ganho do warm start. Só a execução com warm start escreve a solução
+    pub fn compare_warm_start(
+        &self,
+        options: &MipOptions,
+        initial: &Solution,
+        output: &SolutionOutput,
+    ) -> Result<WarmStartReport, MipError> {
+        let cold = self.higgs_solve(options, None, &SolutionOutput::Disabled)?;
+        let warm = self.higgs_solve(options, Some(initial), output)?;
+        // sem a contagem de nós as duas execuções não são comparaveis
+        for (run, result) in [("cold", &cold), ("warm", &warm)] {
+            if result.nodes.is_none() {
+                return Err(MipError::NoNodeCount { run });
+            }
+        }
+        Ok(WarmStartReport {
+            initial_profit: initial.total_profit,
+            cold,
+            warm,
+        })
     }
 }
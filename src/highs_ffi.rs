@@ -0,0 +1,258 @@
+use std::{
+    ffi::{c_void, CString},
+    fmt, ptr,
+};
+
+use highs::HighsModelStatus;
+use highs_sys::*;
+
+use crate::model::MipModel;
+
+// Acesso direto à API C do HiGHS, para o que o crate `highs` não expõe
+// (bound dual, gap, contagem de nós e solução inicial)
+pub(crate) struct Highs(*mut c_void);
+
+// Chamada da API C que retornou erro
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HighsCallError(pub &'static str);
+
+// kHighsSolutionStatusFeasible e kHighsVarTypeInteger de highs_c_api.h
+const SOLUTION_STATUS_FEASIBLE: HighsInt = 2;
+const VAR_TYPE_INTEGER: HighsInt = 1;
+
+// Valor de uma opção do HiGHS, com o tipo que a API C espera
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum OptionValue {
+    Bool(bool),
+    Int(HighsInt),
+    Double(f64),
+    Str(String),
+}
+
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionValue::Bool(value) => write!(f, "{}", if *value { "on" } else { "off" }),
+            OptionValue::Int(value) => write!(f, "{}", value),
+            OptionValue::Double(value) => write!(f, "{}", value),
+            OptionValue::Str(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+fn check(status: HighsInt, call: &'static str) -> Result<(), HighsCallError> {
+    if status == STATUS_ERROR {
+        Err(HighsCallError(call))
+    } else {
+        Ok(())
+    }
+}
+
+fn c_string(value: &str) -> CString {
+    CString::new(value).expect("nome sem byte nulo")
+}
+
+impl Highs {
+    // Instancia silenciosa, como a do crate `highs`
+    pub fn new() -> Self {
+        let mut highs = Highs(unsafe { Highs_create() });
+        // output_flag e log_to_console sempre existem
+        let _ = highs.set_option("output_flag", &OptionValue::Bool(false));
+        let _ = highs.set_option("log_to_console", &OptionValue::Bool(false));
+        highs
+    }
+
+    // Carrega o modelo de maximização, com os nomes de linhas e colunas.
+    // `relaxed` passa as variaveis como continuas em [0, 1] (relaxação linear)
+    pub fn pass_model(&mut self, model: &MipModel, relaxed: bool) -> Result<(), HighsCallError> {
+        let num_col = model.columns.len();
+        let num_row = model.rows.len();
+
+        // matriz por coluna
+        let mut column_entries = vec![Vec::new(); num_col];
+        for (r, row) in model.rows.iter().enumerate() {
+            for &(col, value) in &row.coefficients {
+                column_entries[col].push((r as HighsInt, value));
+            }
+        }
+        let mut a_start = Vec::with_capacity(num_col);
+        let mut a_index = Vec::new();
+        let mut a_value = Vec::new();
+        for entries in &column_entries {
+            a_start.push(a_index.len() as HighsInt);
+            for &(row, value) in entries {
+                a_index.push(row);
+                a_value.push(value);
+            }
+        }
+
+        let col_cost: Vec<f64> = model.columns.iter().map(|c| c.objective).collect();
+        let col_lower = vec![0f64; num_col];
+        let col_upper = vec![1f64; num_col];
+        let row_lower = vec![f64::NEG_INFINITY; num_row];
+        let row_upper: Vec<f64> = model.rows.iter().map(|r| r.upper).collect();
+        let integrality = vec![VAR_TYPE_INTEGER; num_col];
+
+        let status = if relaxed {
+            unsafe {
+                Highs_passLp(
+                    self.0,
+                    num_col as HighsInt,
+                    num_row as HighsInt,
+                    a_value.len() as HighsInt,
+                    MATRIX_FORMAT_COLUMN_WISE,
+                    OBJECTIVE_SENSE_MAXIMIZE,
+                    model.objective_offset,
+                    col_cost.as_ptr(),
+                    col_lower.as_ptr(),
+                    col_upper.as_ptr(),
+                    row_lower.as_ptr(),
+                    row_upper.as_ptr(),
+                    a_start.as_ptr(),
+                    a_index.as_ptr(),
+                    a_value.as_ptr(),
+                )
+            }
+        } else {
+            unsafe {
+                Highs_passMip(
+                    self.0,
+                    num_col as HighsInt,
+                    num_row as HighsInt,
+                    a_value.len() as HighsInt,
+                    MATRIX_FORMAT_COLUMN_WISE,
+                    OBJECTIVE_SENSE_MAXIMIZE,
+                    model.objective_offset,
+                    col_cost.as_ptr(),
+                    col_lower.as_ptr(),
+                    col_upper.as_ptr(),
+                    row_lower.as_ptr(),
+                    row_upper.as_ptr(),
+                    a_start.as_ptr(),
+                    a_index.as_ptr(),
+                    a_value.as_ptr(),
+                    integrality.as_ptr(),
+                )
+            }
+        };
+        check(status, "Highs_passModel")?;
+
+        for (i, column) in model.columns.iter().enumerate() {
+            let name = c_string(&column.name);
+            check(
+                unsafe { Highs_passColName(self.0, i as HighsInt, name.as_ptr()) },
+                "Highs_passColName",
+            )?;
+        }
+        for (i, row) in model.rows.iter().enumerate() {
+            let name = c_string(&row.name);
+            check(
+                unsafe { Highs_passRowName(self.0, i as HighsInt, name.as_ptr()) },
+                "Highs_passRowName",
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn set_option(&mut self, option: &str, value: &OptionValue) -> Result<(), HighsCallError> {
+        let option = c_string(option);
+        let status = match value {
+            OptionValue::Bool(value) => unsafe {
+                Highs_setBoolOptionValue(self.0, option.as_ptr(), *value as HighsInt)
+            },
+            OptionValue::Int(value) => unsafe {
+                Highs_setIntOptionValue(self.0, option.as_ptr(), *value)
+            },
+            OptionValue::Double(value) => unsafe {
+                Highs_setDoubleOptionValue(self.0, option.as_ptr(), *value)
+            },
+            OptionValue::Str(value) => {
+                let value = c_string(value);
+                unsafe { Highs_setStringOptionValue(self.0, option.as_ptr(), value.as_ptr()) }
+            }
+        };
+        check(status, "Highs_setOptionValue")
+    }
+
+    // Solução inicial (incumbente) para o branch and bound, uma entrada por coluna
+    pub fn set_solution(&mut self, column_values: &[f64]) -> Result<(), HighsCallError> {
+        check(
+            unsafe {
+                Highs_setSolution(
+                    self.0,
+                    column_values.as_ptr(),
+                    ptr::null(),
+                    ptr::null(),
+                    ptr::null(),
+                )
+            },
+            "Highs_setSolution",
+        )
+    }
+
+    pub fn run(&mut self) -> Result<(), HighsCallError> {
+        check(unsafe { Highs_run(self.0) }, "Highs_run")
+    }
+
+    pub fn model_status(&self) -> HighsModelStatus {
+        HighsModelStatus::try_from(unsafe { Highs_getModelStatus(self.0) })
+            .unwrap_or(HighsModelStatus::Unknown)
+    }
+
+    // Se o solver terminou com alguma solução viavel (otima ou não)
+    pub fn has_feasible_solution(&self) -> bool {
+        self.int_info("primal_solution_status") == Some(SOLUTION_STATUS_FEASIBLE)
+    }
+
+    pub fn column_values(&self) -> Vec<f64> {
+        let num_col = unsafe { Highs_getNumCols(self.0) }.max(0) as usize;
+        let num_row = unsafe { Highs_getNumRows(self.0) }.max(0) as usize;
+        let mut col_value = vec![0f64; num_col];
+        let mut col_dual = vec![0f64; num_col];
+        let mut row_value = vec![0f64; num_row];
+        let mut row_dual = vec![0f64; num_row];
+        unsafe {
+            Highs_getSolution(
+                self.0,
+                col_value.as_mut_ptr(),
+                col_dual.as_mut_ptr(),
+                row_value.as_mut_ptr(),
+                row_dual.as_mut_ptr(),
+            );
+        }
+        col_value
+    }
+
+    pub fn objective_value(&self) -> f64 {
+        unsafe { Highs_getObjectiveValue(self.0) }
+    }
+
+    pub fn int_info(&self, info: &str) -> Option<HighsInt> {
+        let info = c_string(info);
+        let mut value: HighsInt = 0;
+        let status = unsafe { Highs_getIntInfoValue(self.0, info.as_ptr(), &mut value) };
+        check(status, "Highs_getIntInfoValue").ok().map(|_| value)
+    }
+
+    pub fn int64_info(&self, info: &str) -> Option<i64> {
+        let info = c_string(info);
+        let mut value = 0i64;
+        let status = unsafe { Highs_getInt64InfoValue(self.0, info.as_ptr(), &mut value) };
+        check(status, "Highs_getInt64InfoValue").ok().map(|_| value)
+    }
+
+    pub fn double_info(&self, info: &str) -> Option<f64> {
+        let info = c_string(info);
+        let mut value = 0f64;
+        let status = unsafe { Highs_getDoubleInfoValue(self.0, info.as_ptr(), &mut value) };
+        check(status, "Highs_getDoubleInfoValue")
+            .ok()
+            .map(|_| value)
+    }
+}
+
+impl Drop for Highs {
+    fn drop(&mut self) {
+        unsafe { Highs_destroy(self.0) }
+    }
+}
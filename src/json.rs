@@ -0,0 +1,115 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use crate::{
+    parse::{ParseError, TkpInstance},
+    solution_file::SolutionFileError,
+    tabu_search::Solution,
+};
+
+// Formato json alternativo ao formato de texto das instancias,
+// usado por `parse_from_file` para arquivos `.json`
+impl TkpInstance {
+    pub fn parse_json_file(path: &Path) -> Result<Self, ParseError> {
+        let file = File::open(path).map_err(|source| ParseError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|source| ParseError::Json {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn parse_json_str(contents: &str) -> Result<Self, ParseError> {
+        serde_json::from_str(contents).map_err(|source| ParseError::Json {
+            path: "<json>".into(),
+            source,
+        })
+    }
+
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).expect("instancia sempre serializa")
+    }
+
+    pub fn write_json_file(&self, path: &Path) -> std::io::Result<()> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)
+            .map_err(std::io::Error::from)
+    }
+}
+
+// Campos lidos de uma solução em json; lucro e viabilidade gravados são ignorados
+#[derive(serde::Deserialize)]
+struct SolutionJson {
+    selected_orders: Vec<bool>,
+}
+
+impl Solution {
+    // A demanda por segmento não é serializada, então só a seleção é lida e o resto é
+    // recalculado na instancia, como em `from_selection`
+    pub fn parse_json_str(
+        instance: &TkpInstance,
+        contents: &str,
+    ) -> Result<Self, SolutionFileError> {
+        let solution: SolutionJson =
+            serde_json::from_str(contents).map_err(SolutionFileError::Json)?;
+        if solution.selected_orders.len() != instance.orders.len() {
+            return Err(SolutionFileError::LengthMismatch {
+                expected: instance.orders.len(),
+                found: solution.selected_orders.len(),
+            });
+        }
+        Ok(Solution::from_selection(instance, solution.selected_orders))
+    }
+
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).expect("solução sempre serializa")
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn bundled(name: &str) -> TkpInstance {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tkp_instances")
+            .join(name);
+        TkpInstance::parse_from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn instance_round_trips() {
+        let instance = bundled("U2");
+        let parsed = TkpInstance::parse_json_str(&instance.to_json_string()).unwrap();
+        assert_eq!(parsed, instance);
+    }
+
+    #[test]
+    fn deserialized_solution_is_usable() {
+        let instance = bundled("U2");
+        let solution = instance.tabu_search(500, 10, 10, 1, None).unwrap();
+        let parsed = Solution::parse_json_str(&instance, &solution.to_json_string()).unwrap();
+        // igualdade inclui a demanda por segmento, recalculada na leitura
+        assert_eq!(parsed, solution);
+
+        // uma seleção inviavel é lida como inviavel, não com o lucro gravado
+        let all = Solution::from_selection(&instance, vec![true; instance.orders.len()]);
+        let json = all.to_json_string();
+        assert!(json.contains("\"is_feasible\": false"));
+        let json = json.replace("\"is_feasible\": false", "\"is_feasible\": true");
+        let parsed = Solution::parse_json_str(&instance, &json).unwrap();
+        assert!(!parsed.is_feasible);
+        assert_eq!(parsed.total_profit, all.total_profit);
+
+        assert!(matches!(
+            Solution::parse_json_str(&instance, r#"{"selected_orders": [true]}"#),
+            Err(SolutionFileError::LengthMismatch { found: 1, .. })
+        ));
+    }
+}
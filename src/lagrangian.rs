@@ -0,0 +1,317 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    cliques::CapacityRow, parse::TkpInstance, tabu_search::Solution, validate::ValidationError,
+};
+
+// Relaxação lagrangiana das restrições de capacidade (uma por clique maximal, como em
+// `higgs_solve`): com multiplicadores u_r >= 0 o problema se separa por ordem,
+//
+// L(u) = C sum_r u_r + sum_i max(0, lucro_i - demanda_i sum_{r contem i} u_r)
+//
+// e todo L(u) é um limite superior. Os multiplicadores são ajustados por subgradiente
+// e a seleção relaxada de cada iteração é reparada em uma solução viavel
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagrangianOptions {
+    pub iterations: usize,
+    // fator inicial do passo de Polyak, dividido por 2 a cada `patience` iterações sem melhora
+    pub initial_step: f64,
+    pub patience: usize,
+    pub min_step: f64,
+    // guarda os multiplicadores a cada `history_interval` iterações (0 desliga)
+    pub history_interval: usize,
+}
+
+impl Default for LagrangianOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            initial_step: 2.0,
+            patience: 30,
+            min_step: 1e-4,
+            history_interval: 50,
+        }
+    }
+}
+
+// Estado do subgradiente em uma iteração
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagrangianIteration {
+    pub iteration: usize,
+    // L(u) da iteração
+    pub bound: f64,
+    pub best_bound: f64,
+    pub best_profit: u32,
+    pub step: f64,
+    // multiplicadores usados na iteração, só nas iterações guardadas
+    pub multipliers: Option<Vec<f64>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LagrangianResult {
+    pub upper_bound: f64,
+    pub solution: Solution,
+    // multiplicadores que deram o melhor limite, um por restrição de capacidade
+    pub multipliers: Vec<f64>,
+    // periodo de cada restrição, na mesma ordem de `multipliers`
+    pub periods: Vec<u64>,
+    pub history: Vec<LagrangianIteration>,
+    pub time: Duration,
+}
+
+impl LagrangianResult {
+    // o limite já prova que a solução é otima
+    pub fn is_optimal(&self) -> bool {
+        (self.upper_bound + 1e-6).floor() <= self.solution.total_profit as f64
+    }
+}
+
+impl TkpInstance {
+    pub fn lagrangian(
+        &self,
+        options: &LagrangianOptions,
+    ) -> Result<LagrangianResult, ValidationError> {
+        self.check_valid()?;
+
+        let now = Instant::now();
+        let (rows, _) = self.capacity_rows();
+        let capacity = self.capacity as f64;
+
+        // restrições de cada ordem
+        let mut order_rows = vec![Vec::new(); self.orders.len()];
+        for (r, row) in rows.iter().enumerate() {
+            for &i in &row.orders {
+                order_rows[i].push(r);
+            }
+        }
+
+        let mut multipliers = vec![0f64; rows.len()];
+        let mut best_multipliers = multipliers.clone();
+        // L(0) é a soma dos lucros
+        let mut best_bound: f64 = self.orders.iter().map(|o| o.profit as f64).sum();
+        let mut best_solution: Option<Solution> = None;
+        let mut step_factor = options.initial_step;
+        let mut since_improvement = 0;
+        let mut history = Vec::new();
+        let mut selected = vec![false; self.orders.len()];
+
+        // ordens por lucro / demanda crescente, ordem usada no reparo
+        let ratio = |i: usize| self.orders[i].profit as f64 / self.orders[i].demand.max(1) as f64;
+        let mut by_ratio: Vec<usize> = (0..self.orders.len()).collect();
+        by_ratio.sort_by(|&a, &b| ratio(a).total_cmp(&ratio(b)));
+
+        for iteration in 0..options.iterations {
+            // subproblema: cada ordem entra se o lucro reduzido for positivo
+            let mut bound = capacity * multipliers.iter().sum::<f64>();
+            for (i, order) in self.orders.iter().enumerate() {
+                let price: f64 = order_rows[i].iter().map(|&r| multipliers[r]).sum();
+                let reduced = order.profit as f64 - order.demand as f64 * price;
+                selected[i] = reduced > 0.0;
+                if selected[i] {
+                    bound += reduced;
+                }
+            }
+
+            if bound < best_bound - 1e-9 {
+                best_bound = bound;
+                best_multipliers.clone_from(&multipliers);
+                since_improvement = 0;
+            } else {
+                since_improvement += 1;
+                if since_improvement >= options.patience {
+                    step_factor /= 2.0;
+                    since_improvement = 0;
+                }
+            }
+
+            let repaired = self.repair(&selected, &rows, &order_rows, &by_ratio);
+            if best_solution
+                .as_ref()
+                .is_none_or(|best| repaired.total_profit > best.total_profit)
+            {
+                best_solution = Some(repaired);
+            }
+            let best_profit = best_solution.as_ref().map_or(0, |s| s.total_profit);
+
+            // subgradiente: folga de capacidade em cada restrição
+            let mut subgradient = vec![capacity; rows.len()];
+            for (i, _) in selected.iter().enumerate().filter(|(_, s)| **s) {
+                for &r in &order_rows[i] {
+                    subgradient[r] -= self.orders[i].demand as f64;
+                }
+            }
+            // só as componentes que ainda podem mover o multiplicador contam no passo
+            let norm: f64 = subgradient
+                .iter()
+                .zip(&multipliers)
+                .filter(|(g, u)| **g < 0.0 || **u > 0.0)
+                .map(|(g, _)| g * g)
+                .sum();
+            let step = if norm > 0.0 {
+                step_factor * (bound - best_profit as f64).max(0.0) / norm
+            } else {
+                0.0
+            };
+
+            let record = options.history_interval > 0 && iteration % options.history_interval == 0;
+            history.push(LagrangianIteration {
+                iteration,
+                bound,
+                best_bound,
+                best_profit,
+                step,
+                multipliers: record.then(|| multipliers.clone()),
+            });
+
+            // para quando o limite prova a otimalidade ou o passo some
+            if (best_bound + 1e-6).floor() <= best_profit as f64
+                || step == 0.0
+                || step_factor < options.min_step
+            {
+                break;
+            }
+
+            for (u, g) in multipliers.iter_mut().zip(&subgradient) {
+                *u = (*u - step * g).max(0.0);
+            }
+        }
+
+        let periods = rows.iter().map(|row| row.period).collect();
+        Ok(LagrangianResult {
+            upper_bound: best_bound,
+            solution: best_solution
+                .unwrap_or_else(|| Solution::from_selection(self, vec![false; self.orders.len()])),
+            multipliers: best_multipliers,
+            periods,
+            history,
+            time: now.elapsed(),
+        })
+    }
+
+    // Torna viavel uma seleção relaxada: remove as ordens de pior lucro por demanda
+    // das restrições estouradas e depois completa gulosamente com as que ainda cabem
+    fn repair(
+        &self,
+        selected: &[bool],
+        rows: &[CapacityRow],
+        order_rows: &[Vec<usize>],
+        by_ratio: &[usize],
+    ) -> Solution {
+        let capacity = self.capacity as u64;
+        let mut selected = selected.to_vec();
+        let mut load = vec![0u64; rows.len()];
+        for (i, _) in selected.iter().enumerate().filter(|(_, s)| **s) {
+            for &r in &order_rows[i] {
+                load[r] += self.orders[i].demand as u64;
+            }
+        }
+
+        // numa passada só, em ordem crescente: uma restrição que continuasse estourada
+        // no fim teria tido todas as suas ordens removidas
+        for &i in by_ratio {
+            if selected[i] && order_rows[i].iter().any(|&r| load[r] > capacity) {
+                selected[i] = false;
+                for &r in &order_rows[i] {
+                    load[r] -= self.orders[i].demand as u64;
+                }
+            }
+        }
+
+        for &i in by_ratio.iter().rev() {
+            let demand = self.orders[i].demand as u64;
+            if !selected[i] && order_rows[i].iter().all(|&r| load[r] + demand <= capacity) {
+                selected[i] = true;
+                for &r in &order_rows[i] {
+                    load[r] += demand;
+                }
+            }
+        }
+
+        Solution::from_selection(self, selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        solution_file::{SolutionFile, SolutionMetadata},
+        test_instances::{instance, optimum, random_instances},
+    };
+
+    use super::*;
+
+    // viabilidade conferida pelo verificador independente, não pelo `is_feasible` do solver
+    fn verified(instance: &TkpInstance, solution: &Solution) -> bool {
+        let report = instance
+            .verify(&SolutionFile {
+                metadata: SolutionMetadata::default(),
+                selected_orders: solution.selected_orders.clone(),
+            })
+            .unwrap();
+        report.is_feasible() && report.total_profit == solution.total_profit as u64
+    }
+
+    #[test]
+    fn lagrangian_bounds_dp() {
+        let options = LagrangianOptions {
+            iterations: 200,
+            history_interval: 1,
+            ..LagrangianOptions::default()
+        };
+        for instance in random_instances(5, 200, false) {
+            let expected = optimum(&instance);
+            let result = instance.lagrangian(&options).unwrap();
+            assert!(
+                result.upper_bound + 1e-6 >= expected as f64,
+                "{:?}",
+                instance
+            );
+            assert!(verified(&instance, &result.solution));
+            assert!(result.solution.total_profit <= expected);
+            // a projeção mantem os multiplicadores não negativos
+            for iteration in &result.history {
+                let multipliers = iteration.multipliers.as_ref().unwrap();
+                assert!(multipliers.iter().all(|u| *u >= 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn no_binding_rows_means_no_multipliers() {
+        // tudo cabe: não há restrição que possa estourar, então não há multiplicadores,
+        // e L(0) já é a soma dos lucros e prova a otimalidade na primeira iteração
+        let instance = instance(10, &[(4, 5, 1, 3), (6, 5, 2, 4), (3, 9, 6, 8)]);
+        let result = instance.lagrangian(&LagrangianOptions::default()).unwrap();
+        assert_eq!(result.upper_bound, 13.0);
+        assert_eq!(result.solution.total_profit, 13);
+        assert!(result.is_optimal());
+        assert!(result.multipliers.is_empty());
+        assert_eq!(result.history.len(), 1);
+    }
+
+    #[test]
+    fn multiplier_projected_back_to_zero() {
+        // as duas ordens não cabem juntas: o primeiro passo passa do ponto, nenhuma ordem
+        // entra, e o multiplicador volta para 0 pela projeção antes de convergir
+        let instance = instance(10, &[(10, 6, 1, 2), (8, 6, 1, 2)]);
+        let options = LagrangianOptions {
+            history_interval: 1,
+            ..LagrangianOptions::default()
+        };
+        let result = instance.lagrangian(&options).unwrap();
+        let multipliers: Vec<f64> = result
+            .history
+            .iter()
+            .map(|iteration| iteration.multipliers.as_ref().unwrap()[0])
+            .collect();
+        assert!(multipliers
+            .windows(2)
+            .any(|pair| pair[0] > 0.0 && pair[1] == 0.0));
+
+        // limite da relaxação linear: 10 + 8 * 4/6
+        assert!((result.upper_bound - 46.0 / 3.0).abs() < 1e-3);
+        assert_eq!(result.solution.total_profit, 10);
+        assert!(!result.is_optimal());
+        assert!(verified(&instance, &result.solution));
+    }
+}
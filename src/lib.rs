@@ -0,0 +1,26 @@
+pub mod analysis;
+pub mod branch_bound;
+pub mod cliques;
+pub mod decompose;
+pub mod dominance;
+pub mod dp;
+pub mod flow;
+pub mod generate;
+#[cfg(feature = "highs")]
+pub mod higgs_solve;
+#[cfg(feature = "highs")]
+mod highs_ffi;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod lagrangian;
+pub mod model;
+pub mod parse;
+pub mod presolve;
+pub mod solution_file;
+pub mod tabu_search;
+#[cfg(test)]
+mod test_instances;
+pub mod timeline;
+pub mod validate;
+pub mod verify;
+pub mod write;
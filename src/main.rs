@@ -1,45 +1,704 @@
-use parse::TkpInstance;
+use std::{path::Path, time::Duration};
+
+use rayon::prelude::*;
+use tkp_tbu_search::{
+    analysis::InstanceStats,
+    branch_bound::{BranchBoundOptions, NodeSelection},
+    dp::DEFAULT_MAX_STATES,
+    lagrangian::LagrangianOptions,
+    model::ModelFormat,
+    parse::TkpInstance,
+    solution_file::SolutionFile,
+    tabu_search::gap_to_bound,
+};
+#[cfg(feature = "highs")]
+use tkp_tbu_search::{
+    higgs_solve::{LpSolver, MipError, MipLog, MipOptions},
+    parse::{self, FolderFilter},
+    solution_file::SolutionOutput,
+};
 
-mod higgs_solve;
-mod parse;
-mod tabu_search;
 fn main() {
-    let seed: u64 = rand::random();
-    let instances =
-        parse::TkpInstance::parse_instance_folder(std::path::Path::new("tkp_instances"), seed);
+    let args = std::env::args().collect::<Vec<String>>();
+
+    match args.get(1).map(|x| x.as_str()) {
+        #[cfg(feature = "highs")]
+        None => highs_solve(&[]),
+        #[cfg(feature = "highs")]
+        Some("solve") => highs_solve(&args[2..]),
+        #[cfg(feature = "highs")]
+        Some("bound") => bound(&args[2..]),
+        #[cfg(not(feature = "highs"))]
+        None | Some("solve") | Some("bound") => {
+            eprintln!(
+                "{} requires building with the highs feature, the dp, bnb and lagrangian commands solve without it",
+                args.get(1).map_or("solve", |x| x.as_str())
+            );
+            std::process::exit(2);
+        }
+        Some("verify") => verify(&args[2..]),
+        Some("analyze") => analyze(&args[2..]),
+        Some("scenarios") => tabu_scenarios(&args[2..]),
+        Some("export") => export(&args[2..]),
+        Some("lagrangian") => lagrangian(&args[2..]),
+        Some("dp") => dp(&args[2..]),
+        Some("bnb") => branch_and_bound(&args[2..]),
+        Some(command) => {
+            eprintln!("unknown command {}", command);
+            eprintln!("usage: tkp-tbu-search [solve [options] [instance...] | verify <instance> <solution> | analyze <instance>... [--json] | scenarios <instance> [--bound lp|cliques] [--decompose] [--parallel] | bound <instance> [--cliques] [--solver choose|simplex|ipm] [--results <file.tsv>] | lagrangian <instance> [iterations] | dp <instance>... [--max-states <n>] | bnb <instance>... [--dfs] [--node-limit <n>] [--time-limit <seconds>] [--seed <n>] | export <instance> <model.lp|model.mps> [--dominance]]");
+            std::process::exit(2);
+        }
+    }
+}
+
+// analyze $Instance... [--json]: estatisticas das instancias, uma linha tsv por
+// instancia (ou o quadro completo para uma instancia só)
+fn analyze(args: &[String]) {
+    let json = args.iter().any(|x| x == "--json");
+    let mut stats = Vec::new();
+    for path in args.iter().filter(|x| *x != "--json") {
+        match TkpInstance::parse_from_file(Path::new(path)) {
+            Ok(instance) => stats.push(instance.analyze()),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    if json {
+        #[cfg(feature = "serde")]
+        println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+        #[cfg(not(feature = "serde"))]
+        {
+            eprintln!("--json requires building with the serde feature");
+            std::process::exit(2);
+        }
+    } else if let [stats] = stats.as_slice() {
+        print!("{}", stats);
+    } else {
+        println!("{}", InstanceStats::TABLE_HEADER);
+        for stats in &stats {
+            println!("{}", stats.table_row());
+        }
+    }
+}
+
+// bound $Instance [--cliques] [--solver choose|simplex|ipm] [--results $Tsv]: limite
+// superior da relaxação linear.
+// Com --results, reescreve na saida padrão as linhas do tsv de resultados da busca tabu
+// (colunas iguais a results.tsv) com a coluna gap_to_bound calculada a partir do limite.
+// A primeira coluna é o nome da instancia; linhas de outras instancias são puladas
+#[cfg(feature = "highs")]
+fn bound(args: &[String]) {
+    let usage = "usage: tkp-tbu-search bound <instance> [--cliques] [--solver choose|simplex|ipm] [--results <file.tsv>]";
+    let Some(path) = args.first() else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let clique_cuts = args.iter().any(|x| x == "--cliques");
+    let mut options = MipOptions::default();
+    if let Some(i) = args.iter().position(|x| x == "--solver") {
+        match args.get(i + 1).and_then(|name| LpSolver::from_name(name)) {
+            Some(solver) => options.lp_solver = solver,
+            None => {
+                eprintln!("{}", usage);
+                std::process::exit(2);
+            }
+        }
+    }
+    let results = match args.iter().position(|x| x == "--results") {
+        None => None,
+        Some(i) => match args.get(i + 1) {
+            Some(results) => Some(results),
+            None => {
+                eprintln!("{}", usage);
+                std::process::exit(2);
+            }
+        },
+    };
+
+    let instance = match TkpInstance::parse_from_file(Path::new(path)) {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let upper_bound = match instance.lp_bound(&options, clique_cuts) {
+        Ok(bound) => bound,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let Some(results) = results else {
+        println!("{},{}", instance.name, upper_bound);
+        return;
+    };
+
+    let contents = match std::fs::read_to_string(results) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("{}: {}", results, err);
+            std::process::exit(1);
+        }
+    };
+    let mut lines = contents.lines();
+    let Some(header) = lines.next() else {
+        return;
+    };
+    let Some(profit_column) = header.split('\t').position(|x| x == "total_profit") else {
+        eprintln!("{}: missing total_profit column", results);
+        std::process::exit(1);
+    };
+    println!("{}\tgap_to_bound", header);
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let name = line.split('\t').next().unwrap_or_default();
+        if name != instance.name {
+            eprintln!(
+                "{}: skipping row for instance {:?}, the bound is for {}",
+                results, name, instance.name
+            );
+            continue;
+        }
+        let profit = line
+            .split('\t')
+            .nth(profit_column)
+            .and_then(|x| x.parse::<u32>().ok());
+        match profit {
+            Some(profit) => println!("{}\t{:.4}", line, gap_to_bound(profit, upper_bound)),
+            None => println!("{}\t", line),
+        }
+    }
+}
+
+// lagrangian $Instance [$Iterations]: limite superior e solução reparada da relaxação
+// lagrangiana, sem o HiGHS
+fn lagrangian(args: &[String]) {
+    let usage = "usage: tkp-tbu-search lagrangian <instance> [iterations]";
+    let Some(path) = args.first() else {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    };
+    let mut options = LagrangianOptions::default();
+    if let Some(iterations) = args.get(1) {
+        match iterations.parse() {
+            Ok(iterations) => options.iterations = iterations,
+            Err(_) => {
+                eprintln!("{}", usage);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let result = TkpInstance::parse_from_file(Path::new(path))
+        .map_err(|err| err.to_string())
+        .and_then(|instance| instance.lagrangian(&options).map_err(|err| err.to_string()));
+    match result {
+        Ok(result) => {
+            println!("name,upper_bound,total_profit,gap_to_bound,iterations,time");
+            println!(
+                "{},{:.2},{},{:.4},{},{}ms",
+                path,
+                result.upper_bound,
+                result.solution.total_profit,
+                gap_to_bound(result.solution.total_profit, result.upper_bound),
+                result.history.len(),
+                result.time.as_millis()
+            );
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// dp $Instance... [--max-states $N]: solução exata por programação dinamica, sem o HiGHS.
+// Serve de oracle para conferir os outros solvers em instancias pequenas
+fn dp(args: &[String]) {
+    let mut max_states = DEFAULT_MAX_STATES;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--max-states" {
+            match args.next().and_then(|x| x.parse().ok()) {
+                Some(value) => max_states = value,
+                None => {
+                    eprintln!("--max-states expects a number");
+                    std::process::exit(2);
+                }
+            }
+        } else {
+            paths.push(arg);
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("usage: tkp-tbu-search dp <instance>... [--max-states <n>]");
+        std::process::exit(2);
+    }
+
+    println!("name,total_profit,peak_states,time");
+    for path in paths {
+        let result = TkpInstance::parse_from_file(Path::new(path))
+            .map_err(|err| err.to_string())
+            .and_then(|instance| {
+                instance
+                    .dp_solve(max_states)
+                    .map_err(|err| format!("{}: {}", instance.name, err))
+            });
+        match result {
+            Ok(result) => println!(
+                "{},{},{},{}ms",
+                path,
+                result.solution.total_profit,
+                result.peak_states,
+                result.time.as_millis()
+            ),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
+// bnb $Instance... [--dfs] [--node-limit $N] [--time-limit $Seconds] [--seed $N]: branch and
+// bound sem o HiGHS, partindo da busca tabu. Com limite atingido a solução não é provada otima
+fn branch_and_bound(args: &[String]) {
+    let usage = "usage: tkp-tbu-search bnb <instance>... [--dfs] [--node-limit <n>] [--time-limit <seconds>] [--seed <n>]";
+    let mut options = BranchBoundOptions::default();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--dfs" => {
+                options.node_selection = NodeSelection::DepthFirst;
+                Some(())
+            }
+            "--node-limit" => args
+                .next()
+                .and_then(|x| x.parse().ok())
+                .map(|limit| options.node_limit = limit),
+            "--time-limit" => args
+                .next()
+                .and_then(|x| x.parse().ok())
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .map(|limit| options.time_limit = Some(limit)),
+            "--seed" => args
+                .next()
+                .and_then(|x| x.parse().ok())
+                .map(|seed| options.random_seed = seed),
+            flag if flag.starts_with("--") => None,
+            _ => {
+                paths.push(arg);
+                Some(())
+            }
+        };
+        if parsed.is_none() {
+            eprintln!("invalid option {}", arg);
+            eprintln!("{}", usage);
+            std::process::exit(2);
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    }
+
+    println!("name,total_profit,initial_profit,upper_bound,gap_to_bound,nodes,status,time");
+    for path in paths {
+        let result = TkpInstance::parse_from_file(Path::new(path))
+            .map_err(|err| err.to_string())
+            .and_then(|instance| {
+                instance
+                    .branch_and_bound(&options)
+                    .map_err(|err| err.to_string())
+            });
+        match result {
+            Ok(result) => println!(
+                "{},{},{},{:.2},{:.4},{},{},{}ms",
+                path,
+                result.solution.total_profit,
+                result.initial_profit,
+                result.upper_bound,
+                gap_to_bound(result.solution.total_profit, result.upper_bound),
+                result.nodes,
+                result.status,
+                result.time.as_millis()
+            ),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
+// export $Instance $Model: escreve o modelo MIP usado pelo highs em LP ou MPS,
+// conforme a extensão do arquivo, sem resolver. O presolve é o mesmo do solve, e
+// --dominance adiciona as mesmas restrições de dominancia
+fn export(args: &[String]) {
+    let (Some(instance_path), Some(model_path)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: tkp-tbu-search export <instance> <model.lp|model.mps> [--dominance]");
+        std::process::exit(2);
+    };
+    let dominance = args[2..].iter().any(|x| x == "--dominance");
+    let model_path = Path::new(model_path);
+    let Some(format) = ModelFormat::from_path(model_path) else {
+        eprintln!("{}: expected a .lp or .mps file", model_path.display());
+        std::process::exit(2);
+    };
+
+    let instance = match TkpInstance::parse_from_file(Path::new(instance_path)) {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = instance.check_valid() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    let (model, presolved) = instance.presolved_mip_model(dominance);
+    println!("{}", presolved);
+    if let Err(err) = model.write_to_file(model_path, format) {
+        eprintln!("{}: {}", model_path.display(), err);
+        std::process::exit(1);
+    }
+    println!(
+        "{}: {} columns, {} rows",
+        model_path.display(),
+        model.columns.len(),
+        model.rows.len()
+    );
+}
+
+// verify $Instance $Solution: recalcula lucro e capacidade de uma solução,
+// sai com codigo 1 se for inviavel ou o objetivo declarado não bater
+fn verify(args: &[String]) {
+    let (Some(instance_path), Some(solution_path)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: tkp-tbu-search verify <instance> <solution>");
+        std::process::exit(2);
+    };
+
+    let report = TkpInstance::parse_from_file(Path::new(instance_path))
+        .map_err(|err| err.to_string())
+        .and_then(|instance| {
+            let solution = SolutionFile::read_from_file(Path::new(solution_path))
+                .map_err(|err| err.to_string())?;
+            instance.verify(&solution).map_err(|err| err.to_string())
+        });
+
+    match report {
+        Ok(report) => {
+            print!("{}", report);
+            if !report.is_valid() {
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(feature = "highs")]
+const SOLVE_USAGE: &str = "usage: tkp-tbu-search solve [--time-limit <seconds>] [--rel-gap <gap>] [--abs-gap <gap>] [--threads <n>] [--seed <n>] [--log off|stdout|<file>] [--dominance] [--warm-start | --decompose] [instance...]";
+
+// Lê as opções do HiGHS da linha de comando, devolvendo também os argumentos restantes
+#[cfg(feature = "highs")]
+fn parse_mip_options(args: &[String]) -> Result<(MipOptions, Vec<String>), String> {
+    fn value<T: std::str::FromStr>(name: &str, value: Option<&String>) -> Result<T, String> {
+        let value = value.ok_or(format!("{} expects a value", name))?;
+        value
+            .parse()
+            .map_err(|_| format!("invalid value for {}: {}", name, value))
+    }
+
+    let mut options = MipOptions::default();
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--time-limit" => {
+                let seconds: f64 = value(arg, args.next())?;
+                options.time_limit = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| format!("invalid value for {}: {}", arg, seconds))?,
+                );
+            }
+            "--rel-gap" => options.relative_gap = Some(value(arg, args.next())?),
+            "--abs-gap" => options.absolute_gap = Some(value(arg, args.next())?),
+            "--threads" => options.threads = Some(value(arg, args.next())?),
+            "--seed" => options.random_seed = Some(value(arg, args.next())?),
+            "--log" => {
+                options.log = match value::<String>(arg, args.next())?.as_str() {
+                    "off" => MipLog::Off,
+                    "stdout" => MipLog::Stdout,
+                    path => MipLog::File(path.into()),
+                }
+            }
+            "--dominance" => options.dominance = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ => rest.push(arg.clone()),
+        }
+    }
+    Ok((options, rest))
+}
+
+// solve [opções] [--warm-start | --decompose] [$Instance...]: resolve com o HiGHS as
+// instancias dadas, ou todas as de tkp_instances
+#[cfg(feature = "highs")]
+fn highs_solve(args: &[String]) {
+    let warm_start = args.iter().any(|x| x == "--warm-start");
+    let decompose = args.iter().any(|x| x == "--decompose");
+    if warm_start && decompose {
+        eprintln!("--warm-start and --decompose can't be combined");
+        eprintln!("{}", SOLVE_USAGE);
+        std::process::exit(2);
+    }
+    let args: Vec<String> = args
+        .iter()
+        .filter(|x| *x != "--warm-start" && *x != "--decompose")
+        .cloned()
+        .collect();
+    let (options, paths) = match parse_mip_options(&args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", SOLVE_USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let instances = if paths.is_empty() {
+        match parse::TkpInstance::parse_instance_folder(
+            Path::new("tkp_instances"),
+            &FolderFilter::default(),
+        ) {
+            Ok(instances) => instances,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        paths
+            .iter()
+            .map(|path| TkpInstance::parse_from_file(Path::new(path)))
+            .collect()
+    };
+
+    // arquivos .sol vão para SOLUTION_DIR (padrão: diretorio atual), NO_SOLUTION desabilita
+    let output = if std::env::var("NO_SOLUTION").is_ok() {
+        SolutionOutput::Disabled
+    } else {
+        SolutionOutput::Directory(std::env::var("SOLUTION_DIR").unwrap_or(".".into()).into())
+    };
 
     let now = std::time::Instant::now();
     for instance in instances {
-        let higgs_solution = instance.higgs_solve();
+        // um arquivo invalido não impede os outros de rodar
+        let instance = match instance {
+            Ok(instance) => instance,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+
+        // NORMALIZE remove ordens impossiveis e rebaseia o tempo antes de resolver
+        let instance = if std::env::var("NORMALIZE").is_ok() {
+            instance.normalize().0
+        } else {
+            instance
+        };
+
+        // demanda uniforme tem solução exata por fluxo de custo minimo, sem o MIP
+        match instance.flow_solve() {
+            Ok(Some(solution)) => {
+                println!(
+                    "{}: uniform demand, solving by min-cost flow",
+                    instance.name
+                );
+                output.write(&instance, &solution, "min-cost-flow", None, Vec::new());
+                println!(
+                    "{},{},{}",
+                    instance.name,
+                    solution.total_profit,
+                    now.elapsed().as_millis()
+                );
+                continue;
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        }
+
+        // --decompose: um MIP por componente independente, em paralelo
+        if decompose {
+            match instance.higgs_solve_components(&options, &output) {
+                Ok(solution) => println!(
+                    "{},{},{}",
+                    instance.name,
+                    solution.total_profit,
+                    now.elapsed().as_millis()
+                ),
+                Err(err) => eprintln!("{}", err),
+            }
+            continue;
+        }
+
+        // --warm-start: parte do resultado da busca tabu e compara com a execução do zero
+        let result = if warm_start {
+            let seed = options.random_seed.map_or_else(rand::random, u64::from);
+            instance
+                .tabu_search(20000, 10, 50, seed, None)
+                .map_err(MipError::from)
+                .and_then(|initial| instance.compare_warm_start(&options, &initial, &output))
+                .map(|report| {
+                    print!("{}", report);
+                    report.warm
+                })
+        } else {
+            instance.higgs_solve(&options, None, &output)
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+        // status não otimo (ex: limite de tempo) não interrompe as outras instancias
+        if !result.is_optimal() {
+            eprintln!(
+                "{}: {:?}, gap {}",
+                instance.name,
+                result.status,
+                result.gap.map_or("-".into(), |gap| format!("{:.4}", gap))
+            );
+        }
         println!(
             "{},{},{}",
             instance.name,
-            higgs_solution,
+            result
+                .solution
+                .map_or("-".into(), |s| s.total_profit.to_string()),
             now.elapsed().as_millis()
         );
     }
 }
 
-fn tabu_scenarios(instance: TkpInstance) {
-    println!("name,seed,iterations,tabu_list_size,neighborhood_size,total_profit,time");
+// scenarios $Instance: roda a grade de parametros da busca tabu, uma execução por vez,
+// todas compartilhando a mesma instancia. Com --parallel as execuções rodam em paralelo
+// (os tempos medidos deixam de ser comparaveis), e com --decompose cada execução busca
+// nas componentes independentes em paralelo
+fn tabu_scenarios(args: &[String]) {
+    let Some(path) = args.first() else {
+        eprintln!("usage: tkp-tbu-search scenarios <instance> [--bound lp|cliques] [--decompose] [--parallel]");
+        std::process::exit(2);
+    };
+    let instance = match TkpInstance::parse_from_file(Path::new(path)) {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = instance.check_valid() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    // --bound: limite da relaxação linear (cliques: com desigualdades de clique),
+    // calculado uma vez para preencher gap_to_bound de todas as execuções
+    let upper_bound = match args.iter().position(|x| x == "--bound") {
+        None => None,
+        Some(i) => {
+            let clique_cuts = match args.get(i + 1).map(|x| x.as_str()) {
+                Some("lp") => false,
+                Some("cliques") => true,
+                _ => {
+                    eprintln!("--bound expects lp or cliques");
+                    std::process::exit(2);
+                }
+            };
+            #[cfg(feature = "highs")]
+            match instance.lp_bound(&MipOptions::default(), clique_cuts) {
+                Ok(bound) => Some(bound),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    None
+                }
+            }
+            #[cfg(not(feature = "highs"))]
+            {
+                eprintln!(
+                    "--bound {} requires building with the highs feature",
+                    if clique_cuts { "cliques" } else { "lp" }
+                );
+                std::process::exit(2);
+            }
+        }
+    };
+
+    let decompose = args.iter().any(|x| x == "--decompose");
+    if decompose {
+        println!(
+            "{}: {} independent components",
+            instance.name,
+            instance.components().len()
+        );
+    }
+
+    println!(
+        "name,seed,iterations,tabu_list_size,neighborhood_size,total_profit,time,gap_to_bound"
+    );
 
     let iterations = [5000, 20000];
     let random_seeds = [rand::random::<u64>(), rand::random::<u64>()];
     let tabu_list_size = [10, 50];
     let neighborhood_size = [10, 50];
 
+    let mut scenarios = Vec::new();
     for iterations in iterations.iter() {
         for random_seed in random_seeds.iter() {
             for tabu_list_size in tabu_list_size.iter() {
                 for neighborhood_size in neighborhood_size.iter() {
-                    instance.tabu_search(
+                    scenarios.push((
                         *iterations,
                         *tabu_list_size,
                         *neighborhood_size,
                         *random_seed,
-                    );
+                    ));
                 }
             }
         }
     }
+
+    let run = |(iterations, tabu_list_size, neighborhood_size, random_seed)| {
+        // a instancia já foi validada, cada execução imprime sua linha
+        let _ = if decompose {
+            instance.tabu_search_components(
+                iterations,
+                tabu_list_size,
+                neighborhood_size,
+                random_seed,
+                upper_bound,
+            )
+        } else {
+            instance.tabu_search(
+                iterations,
+                tabu_list_size,
+                neighborhood_size,
+                random_seed,
+                upper_bound,
+            )
+        };
+    };
+    if args.iter().any(|x| x == "--parallel") {
+        scenarios.into_par_iter().for_each(run);
+    } else {
+        scenarios.into_iter().for_each(run);
+    }
 }
@@ -0,0 +1,378 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{cliques::RowCounts, dominance::Dominance, parse::TkpInstance, presolve::Presolved};
+
+// Modelo MIP do TKP independente do solver: `presolved_mip_model` monta o conteudo
+// passado ao HiGHS em `higgs_solve`, que também pode ser exportado em LP (CPLEX) ou
+// MPS livre para outros solvers
+//
+// max  sum lucro_i x_i
+// s.a. sum demanda_i x_i <= capacidade   para cada clique maximal
+//      x_i binario
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipModel {
+    pub name: String,
+    pub columns: Vec<MipColumn>,
+    pub rows: Vec<MipRow>,
+    pub row_counts: RowCounts,
+    // constante somada ao objetivo (lucro das ordens fixadas pelo presolve)
+    pub objective_offset: f64,
+}
+
+// Variavel binaria de seleção de uma ordem
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipColumn {
+    // x_<ordem>
+    pub name: String,
+    // indice da ordem na instancia original
+    pub order: usize,
+    pub objective: f64,
+}
+
+// Restrição `sum coeficiente * x <= upper`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipRow {
+    // cap_t<periodo>
+    pub name: String,
+    // (indice da coluna, coeficiente)
+    pub coefficients: Vec<(usize, f64)>,
+    pub upper: f64,
+}
+
+// Formato de exportação, escolhido pela extensão do arquivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+    Lp,
+    Mps,
+}
+
+impl ModelFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "lp" => Some(ModelFormat::Lp),
+            "mps" => Some(ModelFormat::Mps),
+            _ => None,
+        }
+    }
+}
+
+// quantidade de termos por linha no formato LP, que limita o tamanho das linhas
+const LP_TERMS_PER_LINE: usize = 8;
+
+impl TkpInstance {
+    pub fn mip_model(&self) -> MipModel {
+        let columns = self
+            .orders
+            .iter()
+            .enumerate()
+            .map(|(i, o)| MipColumn {
+                name: format!("x_{}", i),
+                order: i,
+                objective: o.profit as f64,
+            })
+            .collect();
+
+        let (capacity_rows, row_counts) = self.capacity_rows();
+        let rows = capacity_rows
+            .into_iter()
+            .map(|row| MipRow {
+                name: format!("cap_t{}", row.period),
+                coefficients: row
+                    .orders
+                    .iter()
+                    .map(|&i| (i, self.orders[i].demand as f64))
+                    .collect(),
+                upper: self.capacity as f64,
+            })
+            .collect();
+
+        MipModel {
+            name: self.name.clone(),
+            columns,
+            rows,
+            row_counts,
+            objective_offset: 0.0,
+        }
+    }
+
+    // Modelo só com as ordens que o presolve não fixou: o lucro das fixadas entra como
+    // constante no objetivo, e as colunas mantêm o nome da ordem original. Usado tanto
+    // pelos solvers quanto pela exportação, para os dois verem o mesmo modelo
+    pub fn presolved_mip_model(&self, dominance: bool) -> (MipModel, Presolved) {
+        let presolved = self.presolve();
+        let mut model = presolved.instance.mip_model();
+        model.name = self.name.clone();
+        model.objective_offset = presolved.fixed_profit as f64;
+        for (column, &order) in model.columns.iter_mut().zip(&presolved.kept) {
+            column.name = format!("x_{}", order);
+            column.order = order;
+        }
+        if dominance {
+            model.add_dominance_rows(&presolved.instance.dominance());
+        }
+        (model, presolved)
+    }
+}
+
+impl MipModel {
+    // Desigualdades de clique: numa restrição de capacidade, as ordens que duas a duas
+    // não cabem juntas admitem no maximo uma selecionada. Não mudam o MIP, mas
+    // apertam a relaxação linear. Retorna quantas restrições foram adicionadas
+    pub fn add_clique_cuts(&mut self) -> usize {
+        let mut cuts = Vec::new();
+        for row in &self.rows {
+            let mut coefficients = row.coefficients.clone();
+            coefficients.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            // toda ordem com mais da metade da capacidade conflita com as outras do conjunto
+            let mut members = coefficients
+                .iter()
+                .take_while(|(_, value)| *value > row.upper / 2.0)
+                .count();
+            // a maior das restantes entra se não couber junto com a menor do conjunto
+            if members > 0
+                && coefficients
+                    .get(members)
+                    .is_some_and(|(_, value)| value + coefficients[members - 1].1 > row.upper)
+            {
+                members += 1;
+            }
+
+            if members >= 2 {
+                cuts.push(MipRow {
+                    name: row.name.replacen("cap_", "clq_", 1),
+                    coefficients: coefficients[..members]
+                        .iter()
+                        .map(|&(col, _)| (col, 1.0))
+                        .collect(),
+                    upper: 1.0,
+                });
+            }
+        }
+
+        let count = cuts.len();
+        self.rows.extend(cuts);
+        count
+    }
+
+    // Restrições de dominancia x_i <= x_j, uma por par sem os implicados por
+    // transitividade. Retorna quantas restrições foram adicionadas
+    pub fn add_dominance_rows(&mut self, dominance: &Dominance) -> usize {
+        let pairs = dominance.covering_pairs();
+        self.rows
+            .extend(pairs.iter().map(|&(dominated, dominating)| MipRow {
+                name: format!(
+                    "dom_{}_{}",
+                    self.columns[dominated].order, self.columns[dominating].order
+                ),
+                coefficients: vec![(dominated, 1.0), (dominating, -1.0)],
+                upper: 0.0,
+            }));
+        pairs.len()
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W, format: ModelFormat) -> std::io::Result<()> {
+        match format {
+            ModelFormat::Lp => self.write_lp(writer),
+            ModelFormat::Mps => self.write_mps(writer),
+        }
+    }
+
+    pub fn write_to_file(&self, path: &Path, format: ModelFormat) -> std::io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?), format)
+    }
+
+    // Formato LP do CPLEX
+    pub fn write_lp<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "\\ TKP instance {}", self.name)?;
+        writeln!(writer, "Maximize")?;
+        write!(writer, " obj:")?;
+        let objective: Vec<_> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, c.objective))
+            .collect();
+        self.write_lp_terms(&mut writer, &objective)?;
+        if self.objective_offset != 0.0 {
+            write!(writer, " + {}", self.objective_offset)?;
+        }
+        writeln!(writer)?;
+
+        writeln!(writer, "Subject To")?;
+        for row in &self.rows {
+            write!(writer, " {}:", row.name)?;
+            self.write_lp_terms(&mut writer, &row.coefficients)?;
+            writeln!(writer, " <= {}", row.upper)?;
+        }
+
+        writeln!(writer, "Binaries")?;
+        for chunk in self.columns.chunks(LP_TERMS_PER_LINE) {
+            let names: Vec<_> = chunk.iter().map(|c| c.name.as_str()).collect();
+            writeln!(writer, " {}", names.join(" "))?;
+        }
+        writeln!(writer, "End")?;
+        writer.flush()
+    }
+
+    fn write_lp_terms<W: Write>(
+        &self,
+        writer: &mut W,
+        terms: &[(usize, f64)],
+    ) -> std::io::Result<()> {
+        for (k, &(col, value)) in terms.iter().enumerate() {
+            if k > 0 && k % LP_TERMS_PER_LINE == 0 {
+                write!(writer, "\n   ")?;
+            }
+            let sign = if value < 0.0 { '-' } else { '+' };
+            if k == 0 && sign == '+' {
+                write!(writer, " {} {}", value, self.columns[col].name)?;
+            } else {
+                write!(
+                    writer,
+                    " {} {} {}",
+                    sign,
+                    value.abs(),
+                    self.columns[col].name
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // Formato MPS livre (nomes separados por espaço, sem colunas fixas)
+    pub fn write_mps<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        // o MPS é escrito por coluna: coeficientes de cada variavel em cada restrição
+        let mut column_entries = vec![Vec::new(); self.columns.len()];
+        for row in &self.rows {
+            for &(col, value) in &row.coefficients {
+                column_entries[col].push((row.name.as_str(), value));
+            }
+        }
+
+        writeln!(writer, "NAME {}", self.name)?;
+        writeln!(writer, "OBJSENSE")?;
+        writeln!(writer, "    MAX")?;
+        writeln!(writer, "ROWS")?;
+        writeln!(writer, " N obj")?;
+        for row in &self.rows {
+            writeln!(writer, " L {}", row.name)?;
+        }
+
+        writeln!(writer, "COLUMNS")?;
+        writeln!(writer, "    MARKER 'MARKER' 'INTORG'")?;
+        for (column, entries) in self.columns.iter().zip(&column_entries) {
+            writeln!(writer, "    {} obj {}", column.name, column.objective)?;
+            for (row, value) in entries {
+                writeln!(writer, "    {} {} {}", column.name, row, value)?;
+            }
+        }
+        writeln!(writer, "    MARKER 'MARKER' 'INTEND'")?;
+
+        writeln!(writer, "RHS")?;
+        // no MPS a constante do objetivo entra negada no RHS da linha do objetivo
+        if self.objective_offset != 0.0 {
+            writeln!(writer, "    RHS obj {}", -self.objective_offset)?;
+        }
+        for row in &self.rows {
+            writeln!(writer, "    RHS {} {}", row.name, row.upper)?;
+        }
+
+        writeln!(writer, "BOUNDS")?;
+        for column in &self.columns {
+            writeln!(writer, " BV BND {}", column.name)?;
+        }
+        writeln!(writer, "ENDATA")?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate::Preset, test_instances::instance};
+
+    #[test]
+    fn presolved_model_names_columns_by_original_order() {
+        let instance = Preset::U2.scaled(300).generate("U2", 3).unwrap();
+        let (model, presolved) = instance.presolved_mip_model(true);
+        assert_eq!(model.objective_offset, presolved.fixed_profit as f64);
+        assert_eq!(model.columns.len(), presolved.kept.len());
+        for (column, &order) in model.columns.iter().zip(&presolved.kept) {
+            assert_eq!(column.order, order);
+            assert_eq!(column.name, format!("x_{}", order));
+            assert_eq!(column.objective, instance.orders[order].profit as f64);
+        }
+        for row in model.rows.iter().filter(|row| row.name.starts_with("dom_")) {
+            let [(dominated, _), (dominating, _)] = row.coefficients[..] else {
+                panic!("{}", row.name);
+            };
+            let (a, b) = (
+                model.columns[dominated].order,
+                model.columns[dominating].order,
+            );
+            assert_eq!(row.name, format!("dom_{}_{}", a, b));
+            assert!(instance.orders[a].profit <= instance.orders[b].profit);
+        }
+    }
+
+    #[test]
+    fn writes_lp_and_mps_text() {
+        // presolve fixa a ordem 0 (lucro 3 no objetivo) e remove a 3; sobra cap_t5 com 1 e 2
+        let instance = instance(
+            10,
+            &[(3, 4, 1, 3), (5, 4, 2, 6), (6, 7, 5, 8), (9, 11, 1, 2)],
+        );
+        let (model, _) = instance.presolved_mip_model(false);
+
+        let mut lp = Vec::new();
+        model.write_lp(&mut lp).unwrap();
+        let lp = String::from_utf8(lp).unwrap();
+        assert_eq!(
+            lp.lines().collect::<Vec<_>>(),
+            [
+                "\\ TKP instance test",
+                "Maximize",
+                " obj: 5 x_1 + 6 x_2 + 3",
+                "Subject To",
+                " cap_t5: 4 x_1 + 7 x_2 <= 10",
+                "Binaries",
+                " x_1 x_2",
+                "End",
+            ]
+        );
+
+        // no MPS o lucro fixado entra negado no RHS do objetivo
+        let mut mps = Vec::new();
+        model.write_mps(&mut mps).unwrap();
+        let mps = String::from_utf8(mps).unwrap();
+        assert_eq!(
+            mps.lines().collect::<Vec<_>>(),
+            [
+                "NAME test",
+                "OBJSENSE",
+                "    MAX",
+                "ROWS",
+                " N obj",
+                " L cap_t5",
+                "COLUMNS",
+                "    MARKER 'MARKER' 'INTORG'",
+                "    x_1 obj 5",
+                "    x_1 cap_t5 4",
+                "    x_2 obj 6",
+                "    x_2 cap_t5 7",
+                "    MARKER 'MARKER' 'INTEND'",
+                "RHS",
+                "    RHS obj -3",
+                "    RHS cap_t5 10",
+                "BOUNDS",
+                " BV BND x_1",
+                " BV BND x_2",
+                "ENDATA",
+            ]
+        );
+    }
+}
@@ -1,21 +1,23 @@
+use rayon::prelude::*;
 use std::{
+    fmt,
     fs::File,
-    io::{BufRead, BufReader},
-    path::Path,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
 };
 
-use rand::SeedableRng;
-
-#[derive(Debug, Clone)]
+// Dados imutaveis da instancia; o estado aleatorio fica com cada execução dos solvers
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TkpInstance {
     pub order_count: usize,
     pub capacity: u32,
     pub orders: Vec<Order>,
     pub name: String,
-    pub rng: rand::rngs::StdRng,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Order {
     pub profit: u32,
     pub demand: u32,
@@ -23,52 +25,376 @@ pub struct Order {
     pub end: u32,
 }
 
+// Erros de leitura de instancia, sempre com o arquivo e a linha (1-indexada) onde ocorreram
+#[derive(Debug)]
+pub enum ParseError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    BadHeader {
+        path: PathBuf,
+        line: usize,
+        field: &'static str,
+        value: String,
+    },
+    WrongFieldCount {
+        path: PathBuf,
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    NonNumericField {
+        path: PathBuf,
+        line: usize,
+        field: &'static str,
+        value: String,
+    },
+    MissingOrders {
+        path: PathBuf,
+        expected: usize,
+        found: usize,
+    },
+    #[cfg(feature = "serde")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ParseError::BadHeader {
+                path,
+                line,
+                field,
+                value,
+            } => write!(
+                f,
+                "{}:{}: invalid header field `{}`: {:?}",
+                path.display(),
+                line,
+                field,
+                value
+            ),
+            ParseError::WrongFieldCount {
+                path,
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}:{}: expected {} fields, found {}",
+                path.display(),
+                line,
+                expected,
+                found
+            ),
+            ParseError::NonNumericField {
+                path,
+                line,
+                field,
+                value,
+            } => write!(
+                f,
+                "{}:{}: field `{}` is not a number: {:?}",
+                path.display(),
+                line,
+                field,
+                value
+            ),
+            ParseError::MissingOrders {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: expected {} orders, found {}",
+                path.display(),
+                expected,
+                found
+            ),
+            #[cfg(feature = "serde")]
+            ParseError::Json { path, source } => {
+                write!(f, "{}: invalid json: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io { source, .. } => Some(source),
+            #[cfg(feature = "serde")]
+            ParseError::Json { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Filtro de arquivos por nome para `parse_instance_folder`: um arquivo é lido se
+// casa com algum padrão de `include` (ou se `include` é vazio) e com nenhum de `exclude`
+#[derive(Debug, Clone)]
+pub struct FolderFilter {
+    pub include: Vec<glob::Pattern>,
+    pub exclude: Vec<glob::Pattern>,
+}
+
+impl FolderFilter {
+    pub fn new(include: &[&str], exclude: &[&str]) -> Result<Self, glob::PatternError> {
+        let compile = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .map(|p| glob::Pattern::new(p))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn matches(&self, file_name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(file_name)))
+            && !self.exclude.iter().any(|p| p.matches(file_name))
+    }
+}
+
+impl Default for FolderFilter {
+    // ignora arquivos ocultos (.DS_Store) e soluções (.sol)
+    fn default() -> Self {
+        FolderFilter::new(&[], &[".*", "*.sol"]).unwrap()
+    }
+}
+
+const ORDER_FIELDS: [&str; 4] = ["profit", "demand", "start", "end"];
+
 impl Order {
-    pub fn parse_from_line(line: &str) -> Self {
-        let values: Vec<u32> = line
-            .trim()
-            .split(" ")
-            .map(|x| x.parse::<u32>().unwrap())
-            .collect();
-        Order {
+    pub fn parse_from_line(
+        line: &str,
+        path: &Path,
+        line_number: usize,
+    ) -> Result<Self, ParseError> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != ORDER_FIELDS.len() {
+            return Err(ParseError::WrongFieldCount {
+                path: path.to_path_buf(),
+                line: line_number,
+                expected: ORDER_FIELDS.len(),
+                found: fields.len(),
+            });
+        }
+
+        let mut values = [0u32; 4];
+        for (i, (value, field)) in fields.iter().zip(ORDER_FIELDS).enumerate() {
+            values[i] = value
+                .parse::<u32>()
+                .map_err(|_| ParseError::NonNumericField {
+                    path: path.to_path_buf(),
+                    line: line_number,
+                    field,
+                    value: value.to_string(),
+                })?;
+        }
+
+        Ok(Order {
             profit: values[0],
             demand: values[1],
             start: values[2],
             end: values[3],
-        }
+        })
     }
 }
 
 impl TkpInstance {
-    pub fn parse_from_file(path: &Path, seed: u64) -> Self {
-        let file = File::open(path).unwrap();
-        let reader = BufReader::new(file);
+    pub fn parse_from_file(path: &Path) -> Result<Self, ParseError> {
+        #[cfg(feature = "serde")]
+        if path.extension().is_some_and(|ext| ext == "json") {
+            return TkpInstance::parse_json_file(path);
+        }
+
+        let file = File::open(path).map_err(|source| ParseError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        TkpInstance::from_reader(file, path)
+    }
+
+    // Lê uma instancia em memoria, o nome da instancia é `name`
+    pub fn parse_from_str(contents: &str, name: &str) -> Result<Self, ParseError> {
+        let mut instance = TkpInstance::from_reader(contents.as_bytes(), Path::new(name))?;
+        instance.name = name.to_string();
+        Ok(instance)
+    }
+
+    // `path` só é usado para o nome da instancia e nas mensagens de erro
+    pub fn from_reader<R: Read>(reader: R, path: &Path) -> Result<Self, ParseError> {
+        let io_error = |source| ParseError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let reader = BufReader::new(reader);
         let mut lines = reader.lines();
-        let n = lines.next().unwrap().unwrap().parse::<usize>().unwrap();
-        let c = lines.next().unwrap().unwrap().parse::<u32>().unwrap();
-        let orders = lines
-            .take(n)
-            .map(|x| Order::parse_from_line(&x.unwrap()))
-            .collect::<Vec<_>>();
-
-        TkpInstance {
-            order_count: n.try_into().unwrap(),
+
+        let n: usize = parse_header_line(lines.next(), path, 1, "n")?;
+        let c: u32 = parse_header_line(lines.next(), path, 2, "capacity")?;
+
+        // linhas depois das n ordens (estatisticas da instancia) são ignoradas
+        // n vem do arquivo, não reservamos mais que isso de antemão
+        let mut orders = Vec::with_capacity(n.min(1 << 16));
+        for (i, line) in lines.take(n).enumerate() {
+            let line = line.map_err(io_error)?;
+            orders.push(Order::parse_from_line(&line, path, i + 3)?);
+        }
+
+        if orders.len() < n {
+            return Err(ParseError::MissingOrders {
+                path: path.to_path_buf(),
+                expected: n,
+                found: orders.len(),
+            });
+        }
+
+        Ok(TkpInstance {
+            order_count: n,
             capacity: c,
             orders,
-            name: path.file_stem().unwrap().to_str().unwrap().to_string(),
-            rng: rand::rngs::StdRng::seed_from_u64(seed),
-        }
+            name: path
+                .file_stem()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        })
     }
 
-    pub fn parse_instance_folder(path: &Path, seed: u64) -> Vec<Self> {
-        let mut instances = Vec::new();
-        let paths = std::fs::read_dir(path).unwrap();
-        for path in paths {
-            let path = path.unwrap().path();
-            if path.is_file() {
-                instances.push(TkpInstance::parse_from_file(&path, seed));
+    // Lê os arquivos da pasta que passam pelo filtro, em paralelo e em ordem de nome.
+    // Só falha por inteiro se a pasta não puder ser listada; cada arquivo tem seu resultado
+    pub fn parse_instance_folder(
+        path: &Path,
+        filter: &FolderFilter,
+    ) -> Result<Vec<Result<Self, ParseError>>, ParseError> {
+        let io_error = |source| ParseError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(path).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.is_file() && filter.matches(&file_name) {
+                paths.push(path);
             }
         }
-        instances
+        paths.sort();
+
+        Ok(paths
+            .par_iter()
+            .map(|path| TkpInstance::parse_from_file(path))
+            .collect())
+    }
+}
+
+fn parse_header_line<T: std::str::FromStr>(
+    line: Option<std::io::Result<String>>,
+    path: &Path,
+    line_number: usize,
+    field: &'static str,
+) -> Result<T, ParseError> {
+    let line = line
+        .transpose()
+        .map_err(|source| ParseError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?
+        .unwrap_or_default();
+
+    line.trim().parse::<T>().map_err(|_| ParseError::BadHeader {
+        path: path.to_path_buf(),
+        line: line_number,
+        field,
+        value: line.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_header_reports_line() {
+        let err = TkpInstance::parse_from_str("x\n10\n", "test").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::BadHeader {
+                line: 1,
+                field: "n",
+                ..
+            }
+        ));
+
+        let err = TkpInstance::parse_from_str("1\n-10\n5 6 1 4\n", "test").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::BadHeader { line: 2, field: "capacity", ref value, .. } if value == "-10"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "test:2: invalid header field `capacity`: \"-10\""
+        );
+
+        // arquivo vazio: falta a linha do cabeçalho
+        let err = TkpInstance::parse_from_str("", "test").unwrap_err();
+        assert!(matches!(err, ParseError::BadHeader { line: 1, .. }));
+    }
+
+    #[test]
+    fn wrong_field_count_reports_line() {
+        let err =
+            TkpInstance::parse_from_str("3\n10\n5 6 1 4\n5 6 1\n5 6 1 4\n", "test").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::WrongFieldCount {
+                line: 4,
+                expected: 4,
+                found: 3,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "test:4: expected 4 fields, found 3");
+    }
+
+    #[test]
+    fn non_numeric_field_reports_line_and_field() {
+        let err = TkpInstance::parse_from_str("2\n10\n5 6 1 4\n5 6 a 4\n", "test").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::NonNumericField { line: 4, field: "start", ref value, .. } if value == "a"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "test:4: field `start` is not a number: \"a\""
+        );
+    }
+
+    #[test]
+    fn missing_orders_reports_counts() {
+        let err = TkpInstance::parse_from_str("3\n10\n5 6 1 4\n5 6 2 4\n", "test").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::MissingOrders {
+                expected: 3,
+                found: 2,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "test: expected 3 orders, found 2");
+
+        // linhas depois das n ordens são ignoradas
+        let instance = TkpInstance::parse_from_str("1\n10\n5 6 1 4\nstats 1 2\n", "test").unwrap();
+        assert_eq!(instance.orders.len(), 1);
     }
 }
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::{
+    parse::{Order, TkpInstance},
+    tabu_search::Solution,
+};
+
+// Instancia reduzida pelo presolve: ordens que sempre cabem já ficam selecionadas e
+// ordens com demanda acima da capacidade nunca podem ser; os solvers só decidem o resto
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Presolved {
+    pub instance: TkpInstance,
+    // para cada ordem da instancia reduzida, seu indice original
+    pub kept: Vec<usize>,
+    // indices originais das ordens pré-selecionadas
+    pub fixed: Vec<usize>,
+    // indices originais das ordens removidas (demanda > capacidade)
+    pub removed: Vec<usize>,
+    pub fixed_profit: u32,
+    original_order_count: usize,
+}
+
+impl TkpInstance {
+    // Uma ordem sempre cabe se, em todos os periodos do seu intervalo, a demanda de
+    // todas as ordens possiveis juntas não passa da capacidade: selecioná-la nunca
+    // restringe as outras, então ela sai do problema já selecionada
+    pub fn presolve(&self) -> Presolved {
+        let timeline = self.timeline();
+        let possible = |o: &Order| o.demand <= self.capacity;
+
+        let total_demand =
+            timeline.total_demand(
+                self.orders
+                    .iter()
+                    .map(|o| if possible(o) { o.demand } else { 0 }),
+            );
+        // segmentos que podem estourar antes de cada segmento, para testar o intervalo
+        // de uma ordem sem percorrê-lo
+        let mut overloaded_before = vec![0usize; total_demand.len() + 1];
+        for (k, demand) in total_demand.iter().enumerate() {
+            overloaded_before[k + 1] =
+                overloaded_before[k] + (*demand > self.capacity as u64) as usize;
+        }
+
+        let (mut kept, mut fixed, mut removed) = (Vec::new(), Vec::new(), Vec::new());
+        for (i, (order, span)) in self.orders.iter().zip(&timeline.spans).enumerate() {
+            if !possible(order) {
+                removed.push(i);
+            } else if span.is_empty()
+                || overloaded_before[span.end] == overloaded_before[span.start]
+            {
+                fixed.push(i);
+            } else {
+                kept.push(i);
+            }
+        }
+
+        let orders: Vec<Order> = kept.iter().map(|&i| self.orders[i]).collect();
+        Presolved {
+            instance: TkpInstance {
+                order_count: orders.len(),
+                orders,
+                ..self.clone()
+            },
+            fixed_profit: fixed.iter().map(|&i| self.orders[i].profit).sum(),
+            kept,
+            fixed,
+            removed,
+            original_order_count: self.orders.len(),
+        }
+    }
+}
+
+impl Presolved {
+    // Não sobrou nada para os solvers decidirem
+    pub fn is_solved(&self) -> bool {
+        self.kept.is_empty()
+    }
+
+    // Seleção na instancia reduzida a partir de uma seleção da original
+    pub fn reduce_selection(&self, selected_orders: &[bool]) -> Vec<bool> {
+        self.kept.iter().map(|&i| selected_orders[i]).collect()
+    }
+
+    // Solução da instancia original a partir de uma seleção da reduzida,
+    // com as ordens fixadas selecionadas
+    pub fn expand(&self, original: &TkpInstance, selected_orders: &[bool]) -> Solution {
+        let mut selected = vec![false; self.original_order_count];
+        for &i in &self.fixed {
+            selected[i] = true;
+        }
+        for (&i, &value) in self.kept.iter().zip(selected_orders) {
+            selected[i] = value;
+        }
+        Solution::from_selection(original, selected)
+    }
+}
+
+impl fmt::Display for Presolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: presolve fixed {} orders (profit {}), removed {}, kept {}",
+            self.instance.name,
+            self.fixed.len(),
+            self.fixed_profit,
+            self.removed.len(),
+            self.kept.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::{instance, optimum, random_instances};
+
+    #[test]
+    fn classifies_fixed_kept_and_removed() {
+        // 0 só divide periodos com 1 e cabe; 1 e 2 estouram juntas nos periodos 5-6;
+        // 3 nunca cabe e não conta na demanda de 0
+        let instance = instance(
+            10,
+            &[(3, 4, 1, 3), (5, 4, 2, 6), (6, 7, 5, 8), (9, 11, 1, 2)],
+        );
+        let presolved = instance.presolve();
+        assert_eq!(presolved.fixed, [0]);
+        assert_eq!(presolved.kept, [1, 2]);
+        assert_eq!(presolved.removed, [3]);
+        assert_eq!(presolved.fixed_profit, 3);
+        assert_eq!(presolved.instance.order_count, 2);
+        assert_eq!(presolved.instance.orders[1], instance.orders[2]);
+
+        let solution = presolved.expand(&instance, &[false, true]);
+        assert_eq!(solution.selected_orders, [true, false, true, false]);
+        assert_eq!(solution.total_profit, 9);
+    }
+
+    #[test]
+    fn presolved_optimum_matches_full_optimum() {
+        for instance in random_instances(10, 300, false) {
+            let presolved = instance.presolve();
+            let kept_optimum = if presolved.is_solved() {
+                0
+            } else {
+                optimum(&presolved.instance)
+            };
+            assert_eq!(
+                presolved.fixed_profit + kept_optimum,
+                optimum(&instance),
+                "{:?}",
+                instance
+            );
+            assert_eq!(
+                presolved.kept.len() + presolved.fixed.len() + presolved.removed.len(),
+                instance.orders.len()
+            );
+        }
+    }
+}
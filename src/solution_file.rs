@@ -0,0 +1,385 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{parse::TkpInstance, tabu_search::Solution, validate::ValidationError};
+
+// Arquivo de solução: cabeçalho de metadados em linhas `# chave: valor`
+// seguido de uma linha 0/1 por ordem. Arquivos antigos, só com as linhas 0/1,
+// também são lidos (com metadados vazios)
+//
+// # instance: U2
+// # fingerprint: 1f0c2a3b4d5e6f70
+// # objective: 28741
+// # solver: highs
+// # seed: 12345
+// # timestamp: 1700000000
+// # param presolve: on
+// 0
+// 1
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SolutionMetadata {
+    pub instance: Option<String>,
+    pub fingerprint: Option<String>,
+    pub objective: Option<u64>,
+    pub solver: Option<String>,
+    pub seed: Option<u64>,
+    // segundos desde a epoch unix
+    pub timestamp: Option<u64>,
+    pub parameters: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionFile {
+    pub metadata: SolutionMetadata,
+    pub selected_orders: Vec<bool>,
+}
+
+#[derive(Debug)]
+pub enum SolutionFileError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    BadLine {
+        path: PathBuf,
+        line: usize,
+        value: String,
+    },
+    LengthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    FingerprintMismatch {
+        expected: String,
+        found: String,
+    },
+    InvalidInstance(ValidationError),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SolutionFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolutionFileError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SolutionFileError::BadLine { path, line, value } => write!(
+                f,
+                "{}:{}: expected 0 or 1, found {:?}",
+                path.display(),
+                line,
+                value
+            ),
+            SolutionFileError::LengthMismatch { expected, found } => write!(
+                f,
+                "solution has {} orders but the instance has {}",
+                found, expected
+            ),
+            SolutionFileError::FingerprintMismatch { expected, found } => write!(
+                f,
+                "solution was computed for instance {} but this instance is {}",
+                found, expected
+            ),
+            SolutionFileError::InvalidInstance(err) => write!(f, "{}", err),
+            #[cfg(feature = "serde")]
+            SolutionFileError::Json(err) => write!(f, "invalid json: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SolutionFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SolutionFileError::Io { source, .. } => Some(source),
+            SolutionFileError::InvalidInstance(err) => Some(err),
+            #[cfg(feature = "serde")]
+            SolutionFileError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Onde um solver escreve o arquivo da solução encontrada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolutionOutput {
+    Disabled,
+    // escreve `{dir}/{nome da instancia}.sol`
+    Directory(PathBuf),
+    File(PathBuf),
+}
+
+impl SolutionOutput {
+    pub fn path_for(&self, instance: &TkpInstance) -> Option<PathBuf> {
+        match self {
+            SolutionOutput::Disabled => None,
+            SolutionOutput::Directory(dir) => Some(dir.join(format!("{}.sol", instance.name))),
+            SolutionOutput::File(path) => Some(path.clone()),
+        }
+    }
+
+    // Escreve o arquivo da solução com os metadados, se habilitado. Um erro de escrita
+    // só é reportado, não interrompe quem resolveu
+    pub fn write(
+        &self,
+        instance: &TkpInstance,
+        solution: &Solution,
+        solver: &str,
+        seed: Option<u64>,
+        parameters: Vec<(String, String)>,
+    ) {
+        let Some(path) = self.path_for(instance) else {
+            return;
+        };
+        let metadata = SolutionMetadata::new(instance, solution, solver, seed, parameters);
+        if let Err(err) = SolutionFile::new(metadata, solution).write_to_file(&path) {
+            eprintln!("{}: {}", path.display(), err);
+        }
+    }
+}
+
+impl SolutionMetadata {
+    // Metadados de uma solução recem calculada para `instance`
+    pub fn new(
+        instance: &TkpInstance,
+        solution: &Solution,
+        solver: &str,
+        seed: Option<u64>,
+        parameters: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            instance: Some(instance.name.clone()),
+            fingerprint: Some(instance.fingerprint()),
+            objective: Some(solution.total_profit as u64),
+            solver: Some(solver.to_string()),
+            seed,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_secs()),
+            parameters,
+        }
+    }
+}
+
+impl SolutionFile {
+    pub fn new(metadata: SolutionMetadata, solution: &Solution) -> Self {
+        Self {
+            metadata,
+            selected_orders: solution.selected_orders.clone(),
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let metadata = &self.metadata;
+        if let Some(instance) = &metadata.instance {
+            writeln!(writer, "# instance: {}", instance)?;
+        }
+        if let Some(fingerprint) = &metadata.fingerprint {
+            writeln!(writer, "# fingerprint: {}", fingerprint)?;
+        }
+        if let Some(objective) = metadata.objective {
+            writeln!(writer, "# objective: {}", objective)?;
+        }
+        if let Some(solver) = &metadata.solver {
+            writeln!(writer, "# solver: {}", solver)?;
+        }
+        if let Some(seed) = metadata.seed {
+            writeln!(writer, "# seed: {}", seed)?;
+        }
+        if let Some(timestamp) = metadata.timestamp {
+            writeln!(writer, "# timestamp: {}", timestamp)?;
+        }
+        for (name, value) in &metadata.parameters {
+            writeln!(writer, "# param {}: {}", name, value)?;
+        }
+        for selected in &self.selected_orders {
+            writeln!(writer, "{}", *selected as u8)?;
+        }
+        writer.flush()
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> std::io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn read_from_file(path: &Path) -> Result<Self, SolutionFileError> {
+        let file = File::open(path).map_err(|source| SolutionFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        SolutionFile::from_reader(file, path)
+    }
+
+    pub fn from_reader<R: Read>(reader: R, path: &Path) -> Result<Self, SolutionFileError> {
+        let mut metadata = SolutionMetadata::default();
+        let mut selected_orders = Vec::new();
+
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|source| SolutionFileError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+            let line = line.trim();
+
+            if let Some(header) = line.strip_prefix('#') {
+                // chaves desconhecidas são ignoradas
+                if let Some((key, value)) = header.split_once(':') {
+                    let value = value.trim().to_string();
+                    match key.trim() {
+                        "instance" => metadata.instance = Some(value),
+                        "fingerprint" => metadata.fingerprint = Some(value),
+                        "objective" => metadata.objective = value.parse().ok(),
+                        "solver" => metadata.solver = Some(value),
+                        "seed" => metadata.seed = value.parse().ok(),
+                        "timestamp" => metadata.timestamp = value.parse().ok(),
+                        key => {
+                            if let Some(name) = key.strip_prefix("param ") {
+                                metadata.parameters.push((name.trim().to_string(), value));
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+
+            match line {
+                "" => continue,
+                "0" => selected_orders.push(false),
+                "1" => selected_orders.push(true),
+                _ => {
+                    return Err(SolutionFileError::BadLine {
+                        path: path.to_path_buf(),
+                        line: i + 1,
+                        value: line.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(Self {
+            metadata,
+            selected_orders,
+        })
+    }
+
+    // Reconstroi a `Solution` para `instance`, recalculando demanda e lucro.
+    // Falha se a solução for de outra instancia (tamanho ou fingerprint diferentes)
+    pub fn to_solution(&self, instance: &TkpInstance) -> Result<Solution, SolutionFileError> {
+        if self.selected_orders.len() != instance.orders.len() {
+            return Err(SolutionFileError::LengthMismatch {
+                expected: instance.orders.len(),
+                found: self.selected_orders.len(),
+            });
+        }
+
+        if let Some(found) = &self.metadata.fingerprint {
+            let expected = instance.fingerprint();
+            if *found != expected {
+                return Err(SolutionFileError::FingerprintMismatch {
+                    expected,
+                    found: found.clone(),
+                });
+            }
+        }
+
+        instance
+            .check_valid()
+            .map_err(SolutionFileError::InvalidInstance)?;
+
+        Ok(Solution::from_selection(
+            instance,
+            self.selected_orders.clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::instance;
+
+    use super::*;
+
+    fn sample() -> (TkpInstance, Solution) {
+        let instance = instance(10, &[(5, 6, 1, 4), (4, 6, 2, 6), (7, 3, 3, 9)]);
+        let solution = Solution::from_selection(&instance, vec![true, false, true]);
+        (instance, solution)
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let (instance, solution) = sample();
+        let metadata = SolutionMetadata::new(
+            &instance,
+            &solution,
+            "tabu",
+            Some(42),
+            vec![("iterations".to_string(), "100".to_string())],
+        );
+        let file = SolutionFile::new(metadata, &solution);
+
+        let mut contents = Vec::new();
+        file.write_to(&mut contents).unwrap();
+        let read = SolutionFile::from_reader(contents.as_slice(), Path::new("test.sol")).unwrap();
+        assert_eq!(read, file);
+        assert_eq!(read.metadata.objective, Some(12));
+        assert_eq!(read.to_solution(&instance).unwrap(), solution);
+    }
+
+    #[test]
+    fn missing_and_unknown_keys() {
+        let (instance, solution) = sample();
+        let contents = "# instance: test\n# comment without key\n# color: blue\n1\n0\n1\n";
+        let read = SolutionFile::from_reader(contents.as_bytes(), Path::new("test.sol")).unwrap();
+        assert_eq!(
+            read.metadata,
+            SolutionMetadata {
+                instance: Some("test".to_string()),
+                ..SolutionMetadata::default()
+            }
+        );
+        // sem fingerprint a solução é aceita só pelo tamanho
+        assert_eq!(read.to_solution(&instance).unwrap(), solution);
+
+        // arquivo antigo, sem cabeçalho
+        let read =
+            SolutionFile::from_reader("1\n0\n1\n".as_bytes(), Path::new("test.sol")).unwrap();
+        assert_eq!(read.metadata, SolutionMetadata::default());
+        assert_eq!(read.selected_orders, [true, false, true]);
+    }
+
+    #[test]
+    fn wrong_number_of_lines() {
+        let (instance, _) = sample();
+        for contents in ["1\n0\n", "1\n0\n1\n1\n"] {
+            let read =
+                SolutionFile::from_reader(contents.as_bytes(), Path::new("test.sol")).unwrap();
+            assert!(matches!(
+                read.to_solution(&instance),
+                Err(SolutionFileError::LengthMismatch { expected: 3, .. })
+            ));
+        }
+
+        let err =
+            SolutionFile::from_reader("1\n2\n1\n".as_bytes(), Path::new("test.sol")).unwrap_err();
+        assert!(matches!(err, SolutionFileError::BadLine { line: 2, .. }));
+    }
+
+    #[test]
+    fn fingerprint_from_another_instance_is_rejected() {
+        let (instance, solution) = sample();
+        let other = TkpInstance {
+            capacity: 11,
+            ..instance.clone()
+        };
+        let metadata = SolutionMetadata::new(&other, &solution, "tabu", None, Vec::new());
+        let file = SolutionFile::new(metadata, &solution);
+        assert!(matches!(
+            file.to_solution(&instance),
+            Err(SolutionFileError::FingerprintMismatch { .. })
+        ));
+    }
+}
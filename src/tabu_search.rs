@@ -1,41 +1,94 @@
-use rand::{seq::IteratorRandom, Rng, SeedableRng};
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, SeedableRng};
 use std::{
     collections::{BTreeMap, HashSet, VecDeque},
     time::Instant,
 };
 
-use crate::parse::TkpInstance;
+use crate::{parse::TkpInstance, timeline::Timeline, validate::ValidationError};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+// Só é serializada: a leitura em json (`Solution::parse_json_str`) recalcula o resto
+// a partir da seleção
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Solution {
     pub selected_orders: Vec<bool>,
     pub total_profit: u32,
     pub is_feasible: bool,
+    // demanda total por segmento do eixo do tempo comprimido (ver `Timeline`),
+    // estado interno da busca, não é serializado
+    #[cfg_attr(feature = "serde", serde(skip))]
     total_demand: Vec<u32>,
 }
 
 impl Solution {
-    fn new(size: usize, profit: u32, last_order_end: usize) -> Self {
+    fn new(size: usize, profit: u32, segment_count: usize) -> Self {
         Self {
             selected_orders: vec![false; size],
             total_profit: profit,
             is_feasible: true,
-            total_demand: vec![0; last_order_end],
+            total_demand: vec![0; segment_count],
         }
     }
+
+    // Reconstroi uma solução a partir do vetor de seleção, recalculando lucro,
+    // demanda por periodo e viabilidade. A instancia precisa ser valida
+    pub fn from_selection(instance: &TkpInstance, selected_orders: Vec<bool>) -> Self {
+        let timeline = instance.timeline();
+        let mut solution = Solution::new(selected_orders.len(), 0, timeline.segment_count());
+
+        for ((order, span), _) in instance
+            .orders
+            .iter()
+            .zip(timeline.spans.iter())
+            .zip(selected_orders.iter())
+            .filter(|(_, selected)| **selected)
+        {
+            solution.total_profit += order.profit;
+            for k in span.clone() {
+                solution.total_demand[k] += order.demand;
+            }
+        }
+
+        solution.is_feasible = solution
+            .total_demand
+            .iter()
+            .all(|demand| *demand <= instance.capacity);
+        solution.selected_orders = selected_orders;
+        solution
+    }
 }
 
-struct TabuSearch {
+// Distancia relativa de um lucro até um limite superior (ex: `lp_bound`), com o
+// limite arredondado para baixo já que o lucro é inteiro
+pub fn gap_to_bound(profit: u32, upper_bound: f64) -> f64 {
+    let bound = (upper_bound + 1e-6).floor();
+    if bound <= 0.0 {
+        return 0.0;
+    }
+    (bound - profit as f64) / bound
+}
+
+// Estado de uma execução: a instancia é só lida, o rng pertence à execução,
+// então várias buscas podem compartilhar a mesma instancia
+struct TabuSearch<'a> {
     tabu_set: HashSet<Solution>,
     tabu_list: VecDeque<Solution>,
     tabu_list_size: usize,
-    tkp_instance: TkpInstance,
+    tkp_instance: &'a TkpInstance,
+    rng: StdRng,
+    timeline: Timeline,
     neighborhood_size: usize,
     pub cost_benefit: BTreeMap<u32, usize>,
     pub selected_for_profit_pool: Vec<usize>,
     pub selected_for_slack_fill: Vec<usize>,
     pub disable_cost_benefit: bool,
     pub disable_slack_fill: bool,
+    // lucro das ordens fixadas pelo presolve, somado no log de melhores soluções
+    pub fixed_profit: u32,
+    // imprime cada nova melhor solução, se IGNORE_BEST não estiver definida
+    pub log_progress: bool,
+    // para cada ordem, as ordens que a dominam (ver `dominance`)
+    dominators: Vec<Vec<usize>>,
 }
 
 impl TkpInstance {
@@ -45,30 +98,130 @@ impl TkpInstance {
         tabu_list_size: usize,
         neighborhood_size: usize,
         random_seed: u64,
-    ) -> Solution {
+        upper_bound: Option<f64>,
+    ) -> Result<Solution, ValidationError> {
+        self.run_tabu_search(
+            iterations,
+            tabu_list_size,
+            neighborhood_size,
+            random_seed,
+            upper_bound,
+            false,
+        )
+    }
+
+    // A mesma busca em cada componente independente (ver `components`), em paralelo.
+    // Cada componente recebe uma parte das iterações proporcional ao seu numero de ordens
+    pub fn tabu_search_components(
+        &self,
+        iterations: usize,
+        tabu_list_size: usize,
+        neighborhood_size: usize,
+        random_seed: u64,
+        upper_bound: Option<f64>,
+    ) -> Result<Solution, ValidationError> {
+        self.run_tabu_search(
+            iterations,
+            tabu_list_size,
+            neighborhood_size,
+            random_seed,
+            upper_bound,
+            true,
+        )
+    }
+
+    fn run_tabu_search(
+        &self,
+        iterations: usize,
+        tabu_list_size: usize,
+        neighborhood_size: usize,
+        random_seed: u64,
+        upper_bound: Option<f64>,
+        components: bool,
+    ) -> Result<Solution, ValidationError> {
+        self.check_valid()?;
+
         let instant = std::time::Instant::now();
-        let cloned = TkpInstance {
-            rng: rand::rngs::StdRng::seed_from_u64(random_seed),
-            ..self.clone()
+        let result = if components {
+            self.solve_components(|component| {
+                let share = (iterations * component.orders.len()).div_ceil(self.orders.len());
+                Ok::<_, ValidationError>(component.presolved_tabu_search(
+                    share,
+                    tabu_list_size,
+                    neighborhood_size,
+                    random_seed,
+                    false,
+                ))
+            })?
+        } else {
+            self.presolved_tabu_search(
+                iterations,
+                tabu_list_size,
+                neighborhood_size,
+                random_seed,
+                true,
+            )
         };
-        let mut tabu_search = TabuSearch::new(tabu_list_size, neighborhood_size, cloned);
-        let result = tabu_search.tabu_search(iterations);
+        // gap_to_bound fica vazio sem limite superior
         println!(
-            "{},{},{},{},{},{},{}ms",
+            "{},{},{},{},{},{},{}ms,{}",
             self.name,
             random_seed,
             iterations,
             tabu_list_size,
             neighborhood_size,
             result.total_profit,
-            instant.elapsed().as_millis()
+            instant.elapsed().as_millis(),
+            upper_bound.map_or(String::new(), |bound| format!(
+                "{:.4}",
+                gap_to_bound(result.total_profit, bound)
+            ))
+        );
+        Ok(result)
+    }
+
+    // A busca só decide as ordens que o presolve não fixou
+    pub(crate) fn presolved_tabu_search(
+        &self,
+        iterations: usize,
+        tabu_list_size: usize,
+        neighborhood_size: usize,
+        random_seed: u64,
+        log_progress: bool,
+    ) -> Solution {
+        let presolved = self.presolve();
+        if log_progress {
+            eprintln!("{}", presolved);
+        }
+        if presolved.is_solved() {
+            return presolved.expand(self, &[]);
+        }
+
+        let mut tabu_search = TabuSearch::new(
+            tabu_list_size,
+            neighborhood_size,
+            &presolved.instance,
+            random_seed,
         );
-        result
+        tabu_search.fixed_profit = presolved.fixed_profit;
+        tabu_search.log_progress = log_progress;
+        let dominance = presolved.instance.dominance();
+        if log_progress {
+            eprintln!("{}", dominance);
+        }
+        tabu_search.dominators = dominance.dominators;
+        let reduced = tabu_search.tabu_search(iterations);
+        presolved.expand(self, &reduced.selected_orders)
     }
 }
 
-impl TabuSearch {
-    fn new(tabu_list_size: usize, neighborhood_size: usize, tkp_instance: TkpInstance) -> Self {
+impl<'a> TabuSearch<'a> {
+    fn new(
+        tabu_list_size: usize,
+        neighborhood_size: usize,
+        tkp_instance: &'a TkpInstance,
+        random_seed: u64,
+    ) -> Self {
         let cost_benefit = tkp_instance.orders.iter().enumerate().map(|(i, x)| {
             (
                 (x.profit as f32 / (x.end - x.start) as f32).round() as u32,
@@ -80,13 +233,18 @@ impl TabuSearch {
             tabu_set: HashSet::with_capacity(tabu_list_size),
             tabu_list: VecDeque::with_capacity(tabu_list_size),
             tabu_list_size,
-            tkp_instance: tkp_instance.clone(),
+            timeline: tkp_instance.timeline(),
+            tkp_instance,
+            rng: StdRng::seed_from_u64(random_seed),
             cost_benefit: cost_benefit.collect(),
             neighborhood_size,
             selected_for_profit_pool: Vec::new(),
             selected_for_slack_fill: Vec::new(),
             disable_cost_benefit: false,
             disable_slack_fill: false,
+            fixed_profit: 0,
+            log_progress: true,
+            dominators: vec![Vec::new(); tkp_instance.orders.len()],
         }
     }
 
@@ -107,17 +265,11 @@ impl TabuSearch {
     pub fn tabu_search(&mut self, iterations: usize) -> Solution {
         let now = Instant::now();
 
-        // Descobre quando ultimo pedido termina
-        let last_order_end = self
-            .tkp_instance
-            .orders
-            .iter()
-            .map(|x| x.end)
-            .max()
-            .unwrap();
-
-        let mut best_solution =
-            Solution::new(self.tkp_instance.orders.len(), 0, last_order_end as usize);
+        let mut best_solution = Solution::new(
+            self.tkp_instance.orders.len(),
+            0,
+            self.timeline.segment_count(),
+        );
         let mut current_solution = best_solution.clone();
 
         for _ in 0..iterations {
@@ -140,11 +292,11 @@ impl TabuSearch {
             {
                 current_solution = best_neighbor.clone();
                 if current_solution.total_profit > best_solution.total_profit {
-                    if std::env::var("IGNORE_BEST").is_err() {
+                    if self.log_progress && std::env::var("IGNORE_BEST").is_err() {
                         println!(
                             "{}ms - Nova melhor solução: {} [{}... (+{})]",
                             now.elapsed().as_millis(),
-                            current_solution.total_profit,
+                            current_solution.total_profit + self.fixed_profit,
                             current_solution
                                 .selected_orders
                                 .iter()
@@ -154,8 +306,12 @@ impl TabuSearch {
                                 .take(15)
                                 .collect::<Vec<_>>()
                                 .join(" "),
-                            (best_solution.selected_orders.iter().filter(|x| **x).count() - 10)
-                                .max(0)
+                            best_solution
+                                .selected_orders
+                                .iter()
+                                .filter(|x| **x)
+                                .count()
+                                .saturating_sub(10)
                         );
                     }
 
@@ -193,17 +349,15 @@ impl TabuSearch {
             .filter(|(_, idx)| {
                 !current_solution.selected_orders[**idx]
                     && !self.selected_for_profit_pool.contains(idx)
+                    && !has_free_dominator(&self.dominators[**idx], current_solution)
             })
             .filter(|(_, idx)| {
                 // seleciona apenas ordens que não ultrapassam a capacidade
                 let order = &self.tkp_instance.orders[**idx];
                 let mut is_feasible = true;
 
-                for t in order.start..=order.end {
-                    let period_index = (t - 1) as usize;
-
-                    if current_solution.total_demand[period_index] + order.demand
-                        > self.tkp_instance.capacity
+                for k in self.timeline.spans[**idx].clone() {
+                    if current_solution.total_demand[k] + order.demand > self.tkp_instance.capacity
                     {
                         is_feasible = false;
                         break;
@@ -215,7 +369,7 @@ impl TabuSearch {
             .map(|(_, idx)| idx)
             // seleciona uma das 5 melhores opções
             .take(5)
-            .choose(&mut self.tkp_instance.rng);
+            .choose(&mut self.rng);
 
         if selected.is_none() {
             return self.generate_random_neighbor(current_solution);
@@ -228,7 +382,7 @@ impl TabuSearch {
         let mut neighbor = current_solution.clone();
         neighbor.is_feasible = true;
         neighbor.selected_orders[*selected_idx] = true;
-        neighbor.total_profit += self.tkp_instance.orders[*selected_idx].profit as u32;
+        neighbor.total_profit += self.tkp_instance.orders[*selected_idx].profit;
 
         self.update_neighbor_total_demand(neighbor, *selected_idx, true, false)
     }
@@ -250,17 +404,15 @@ impl TabuSearch {
             .filter(|(idx, _)| {
                 !current_solution.selected_orders[*idx]
                     && !self.selected_for_slack_fill.contains(idx)
+                    && !has_free_dominator(&self.dominators[*idx], current_solution)
             })
             .filter(|(idx, _)| {
                 // seleciona apenas ordens que não ultrapassam a capacidade
                 let order = &self.tkp_instance.orders[*idx];
                 let mut is_feasible = true;
 
-                for t in order.start..=order.end {
-                    let period_index = (t - 1) as usize;
-
-                    if current_solution.total_demand[period_index] + order.demand
-                        > self.tkp_instance.capacity
+                for k in self.timeline.spans[*idx].clone() {
+                    if current_solution.total_demand[k] + order.demand > self.tkp_instance.capacity
                     {
                         is_feasible = false;
                         break;
@@ -269,10 +421,12 @@ impl TabuSearch {
 
                 is_feasible
             })
-            .map(|(i, x)| {
-                let total_order_slack_fill = (x.start..=x.end)
-                    .map(|t| slack[(t - 1) as usize])
-                    .sum::<u32>();
+            .map(|(i, _)| {
+                // folga de cada segmento pesada pelo numero de periodos que ele cobre
+                let total_order_slack_fill = self.timeline.spans[i]
+                    .clone()
+                    .map(|k| slack[k] as u64 * self.timeline.segment_length(k))
+                    .sum::<u64>();
 
                 (i, total_order_slack_fill)
             })
@@ -280,11 +434,7 @@ impl TabuSearch {
 
         orders_by_slack.sort_unstable_by_key(|x| x.1);
 
-        let selected = orders_by_slack
-            .iter()
-            .rev()
-            .take(5)
-            .choose(&mut self.tkp_instance.rng);
+        let selected = orders_by_slack.iter().rev().take(5).choose(&mut self.rng);
 
         if selected.is_none() {
             return self.generate_random_neighbor(current_solution);
@@ -297,34 +447,45 @@ impl TabuSearch {
         neighbor.is_feasible = true;
 
         neighbor.selected_orders[selected_idx] = true;
-        neighbor.total_profit += self.tkp_instance.orders[selected_idx].profit as u32;
+        neighbor.total_profit += self.tkp_instance.orders[selected_idx].profit;
         self.update_neighbor_total_demand(neighbor, selected_idx, true, false)
     }
 
     fn generate_random_neighbor(&mut self, current_solution: &Solution) -> Solution {
         let mut neighbor = current_solution.clone();
-        let idx = self
-            .tkp_instance
-            .rng
-            .gen_range(0..self.tkp_instance.orders.len());
+        let idx = self.rng.gen_range(0..self.tkp_instance.orders.len());
 
         // se selecionado, deseleciona e calcula lucro total
         if neighbor.selected_orders[idx] {
             neighbor.selected_orders[idx] = false;
             neighbor.total_profit =
-                current_solution.total_profit - self.tkp_instance.orders[idx].profit as u32;
+                current_solution.total_profit - self.tkp_instance.orders[idx].profit;
 
             return self.update_neighbor_total_demand(neighbor, idx, false, true);
         }
 
-        // se deselecionado, seleciona e calcula lucro total
+        // se deselecionado, seleciona e calcula lucro total, trocando uma ordem
+        // dominada pela dominante que estiver fora da solução
+        let idx = self.undominated(current_solution, idx);
         neighbor.selected_orders[idx] = true;
         neighbor.total_profit =
-            current_solution.total_profit + self.tkp_instance.orders[idx].profit as u32;
+            current_solution.total_profit + self.tkp_instance.orders[idx].profit;
 
         self.update_neighbor_total_demand(neighbor, idx, false, false)
     }
 
+    // Ordem a adicionar no lugar de `idx`: sobe pelas dominantes fora da solução,
+    // a relação não tem ciclos
+    fn undominated(&self, solution: &Solution, mut idx: usize) -> usize {
+        while let Some(&j) = self.dominators[idx]
+            .iter()
+            .find(|&&j| !solution.selected_orders[j])
+        {
+            idx = j;
+        }
+        idx
+    }
+
     fn update_neighbor_total_demand(
         &mut self,
         mut neighbor: Solution,
@@ -334,21 +495,19 @@ impl TabuSearch {
     ) -> Solution {
         neighbor.is_feasible = true;
 
-        for t in self.tkp_instance.orders[idx].start..=self.tkp_instance.orders[idx].end {
-            let period_index = (t - 1) as usize;
-
+        for k in self.timeline.spans[idx].clone() {
             if subtract {
-                neighbor.total_demand[period_index] -= self.tkp_instance.orders[idx].demand;
+                neighbor.total_demand[k] -= self.tkp_instance.orders[idx].demand;
             } else {
-                neighbor.total_demand[period_index] += self.tkp_instance.orders[idx].demand;
+                neighbor.total_demand[k] += self.tkp_instance.orders[idx].demand;
             }
-            if neighbor.total_demand[period_index] as u32 > self.tkp_instance.capacity {
+            if neighbor.total_demand[k] > self.tkp_instance.capacity {
                 neighbor.is_feasible = false;
             }
         }
 
         if should_be_feasible {
-            assert_eq!(neighbor.is_feasible, true);
+            assert!(neighbor.is_feasible);
         }
 
         neighbor
@@ -356,7 +515,7 @@ impl TabuSearch {
 
     fn generate_neighbor(&mut self, current_solution: &Solution) -> Solution {
         if self.disable_cost_benefit {
-            let random_strategy = self.tkp_instance.rng.gen_range(0..=1);
+            let random_strategy = self.rng.gen_range(0..=1);
             return match random_strategy {
                 0 => self.generate_random_neighbor(current_solution),
                 1 => self.generate_slack_fill(current_solution),
@@ -365,7 +524,7 @@ impl TabuSearch {
         }
 
         if self.disable_slack_fill {
-            let random_strategy = self.tkp_instance.rng.gen_range(0..=1);
+            let random_strategy = self.rng.gen_range(0..=1);
             return match random_strategy {
                 0 => self.generate_random_neighbor(current_solution),
                 1 => self.generate_best_profit_pool(current_solution),
@@ -377,7 +536,7 @@ impl TabuSearch {
             return self.generate_random_neighbor(current_solution);
         }
 
-        let random_strategy = self.tkp_instance.rng.gen_range(0..=2);
+        let random_strategy = self.rng.gen_range(0..=2);
         match random_strategy {
             0 => self.generate_random_neighbor(current_solution),
             1 => self.generate_best_profit_pool(current_solution),
@@ -386,3 +545,23 @@ impl TabuSearch {
         }
     }
 }
+
+// Adicionar uma ordem com uma dominante fora da solução é sempre pior que adicionar a
+// dominante, que também cabe; esses movimentos ficam fora da vizinhança
+fn has_free_dominator(dominators: &[usize], solution: &Solution) -> bool {
+    dominators.iter().any(|&j| !solution.selected_orders[j])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::{optimum, random_instances};
+
+    #[test]
+    fn tabu_search_stays_below_dp() {
+        for (seed, instance) in random_instances(6, 200, false).enumerate() {
+            let solution = instance.tabu_search(200, 5, 5, seed as u64, None).unwrap();
+            assert!(solution.is_feasible);
+            assert!(solution.total_profit <= optimum(&instance));
+        }
+    }
+}
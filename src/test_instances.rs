@@ -0,0 +1,86 @@
+// Instancias aleatorias pequenas para os testes dos solvers: cabem na força bruta,
+// e o `dp_solve` serve de oraculo para os outros solvers
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    dp::DEFAULT_MAX_STATES,
+    parse::{Order, TkpInstance},
+    tabu_search::Solution,
+};
+
+// Instancia com as ordens `(lucro, demanda, inicio, fim)`
+pub(crate) fn instance(capacity: u32, orders: &[(u32, u32, u32, u32)]) -> TkpInstance {
+    TkpInstance {
+        order_count: orders.len(),
+        capacity,
+        orders: orders
+            .iter()
+            .map(|&(profit, demand, start, end)| Order {
+                profit,
+                demand,
+                start,
+                end,
+            })
+            .collect(),
+        name: "test".to_string(),
+    }
+}
+
+// `count` instancias com até 12 ordens, intervalos curtos em um horizonte curto para
+// haver sobreposição; com `uniform_demand` todas as ordens têm a mesma demanda
+pub(crate) fn random_instances(
+    seed: u64,
+    count: usize,
+    uniform_demand: bool,
+) -> impl Iterator<Item = TkpInstance> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count).map(move |_| random_instance(&mut rng, uniform_demand))
+}
+
+fn random_instance(rng: &mut StdRng, uniform_demand: bool) -> TkpInstance {
+    let order_count = rng.gen_range(1..=12);
+    let capacity = rng.gen_range(1..=20);
+    let demand = rng.gen_range(1..=capacity);
+    let orders: Vec<Order> = (0..order_count)
+        .map(|_| {
+            let start = rng.gen_range(1..=15);
+            Order {
+                profit: rng.gen_range(1..=50),
+                demand: if uniform_demand {
+                    demand
+                } else {
+                    rng.gen_range(1..=capacity)
+                },
+                start,
+                end: start + rng.gen_range(0..=5),
+            }
+        })
+        .collect();
+    TkpInstance {
+        order_count,
+        capacity,
+        orders,
+        name: "random".to_string(),
+    }
+}
+
+pub(crate) fn brute_force(instance: &TkpInstance) -> u32 {
+    (0u32..1 << instance.orders.len())
+        .map(|mask| {
+            let selected = (0..instance.orders.len())
+                .map(|i| mask & (1 << i) != 0)
+                .collect();
+            Solution::from_selection(instance, selected)
+        })
+        .filter(|solution| solution.is_feasible)
+        .map(|solution| solution.total_profit)
+        .max()
+        .unwrap()
+}
+
+// Lucro otimo pela programação dinamica
+pub(crate) fn optimum(instance: &TkpInstance) -> u32 {
+    let solution = instance.dp_solve(DEFAULT_MAX_STATES).unwrap().solution;
+    assert!(solution.is_feasible);
+    solution.total_profit
+}
@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+use crate::parse::{Order, TkpInstance};
+
+// Eixo do tempo comprimido: cada inicio e cada fim + 1 de ordem viram um ponto,
+// e entre dois pontos consecutivos (um segmento) o conjunto de ordens ativas não muda.
+// Os solvers trabalham sobre segmentos, então o custo depende do numero de ordens
+// e não do tamanho do horizonte
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    // o segmento k cobre os periodos [points[k], points[k + 1])
+    pub points: Vec<u64>,
+    // segmentos cobertos por cada ordem, na mesma ordem de `orders`
+    pub spans: Vec<Range<usize>>,
+}
+
+impl Timeline {
+    pub fn new(orders: &[Order]) -> Self {
+        let mut points: Vec<u64> = orders
+            .iter()
+            .flat_map(|o| [o.start as u64, o.end as u64 + 1])
+            .collect();
+        points.sort_unstable();
+        points.dedup();
+
+        let index = |p: u64| points.binary_search(&p).unwrap();
+        // fim antes do inicio gera um intervalo vazio
+        let spans = orders
+            .iter()
+            .map(|o| index(o.start as u64)..index(o.end as u64 + 1))
+            .collect();
+
+        Self { points, spans }
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    // numero de periodos originais dentro do segmento k
+    pub fn segment_length(&self, k: usize) -> u64 {
+        self.points[k + 1] - self.points[k]
+    }
+
+    // primeiro periodo original do segmento k
+    pub fn segment_start(&self, k: usize) -> u64 {
+        self.points[k]
+    }
+
+    // Demanda somada das ordens ativas em cada segmento, com `demands` na mesma ordem de
+    // `spans`. Usa diferenças acumuladas sobre os pontos em vez de percorrer os segmentos
+    // de cada ordem
+    pub fn total_demand(&self, demands: impl IntoIterator<Item = u32>) -> Vec<u64> {
+        let mut delta = vec![0i64; self.points.len()];
+        for (demand, span) in demands.into_iter().zip(&self.spans) {
+            if !span.is_empty() {
+                delta[span.start] += demand as i64;
+                delta[span.end] -= demand as i64;
+            }
+        }
+        delta
+            .iter()
+            .take(self.segment_count())
+            .scan(0i64, |demand, d| {
+                *demand += d;
+                Some(*demand as u64)
+            })
+            .collect()
+    }
+}
+
+impl TkpInstance {
+    pub fn timeline(&self) -> Timeline {
+        Timeline::new(&self.orders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::instance;
+
+    #[test]
+    fn points_are_deduplicated() {
+        // o fim + 1 da primeira ordem é o inicio da segunda, e a terceira repete a primeira
+        let timeline = instance(10, &[(1, 1, 1, 4), (1, 1, 5, 8), (1, 1, 1, 4)]).timeline();
+        assert_eq!(timeline.points, [1, 5, 9]);
+        assert_eq!(timeline.spans, [0..1, 1..2, 0..1]);
+        assert_eq!(timeline.segment_count(), 2);
+        assert_eq!(timeline.segment_length(1), 4);
+    }
+
+    #[test]
+    fn segments_reach_past_u32_max() {
+        let timeline = instance(10, &[(1, 1, 0, u32::MAX), (1, 1, u32::MAX, u32::MAX)]).timeline();
+        assert_eq!(timeline.points, [0, u32::MAX as u64, 1 << 32]);
+        assert_eq!(timeline.segment_start(0), 0);
+        assert_eq!(timeline.segment_length(0), u32::MAX as u64);
+        assert_eq!(timeline.segment_start(1), u32::MAX as u64);
+        assert_eq!(timeline.segment_length(1), 1);
+        assert_eq!(timeline.total_demand([3, 4]), [3, 7]);
+    }
+
+    #[test]
+    fn orders_ending_before_start_have_empty_spans() {
+        let timeline = instance(10, &[(1, 2, 1, 6), (1, 5, 4, 3), (1, 7, 5, 2)]).timeline();
+        assert!(timeline.spans[1].is_empty());
+        assert!(timeline.spans[2].is_empty());
+        assert_eq!(timeline.spans[0], 0..4);
+        // as ordens vazias não entram na demanda de nenhum segmento
+        assert_eq!(timeline.total_demand([2, 5, 7]), [2, 2, 2, 2]);
+    }
+}
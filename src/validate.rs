@@ -0,0 +1,313 @@
+use std::fmt;
+
+use crate::parse::{Order, TkpInstance};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    // a instancia não pode ser resolvida como está
+    Error,
+    // a instancia é resolvivel, mas tem ordens inúteis
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    EmptyInstance,
+    OrderCountMismatch { declared: usize, found: usize },
+    // os solvers usam o eixo do tempo comprimido, mas os arquivos originais começam em 1
+    ZeroStart { order: usize },
+    EndBeforeStart { order: usize, start: u32, end: u32 },
+    // start == end: duração zero no calculo de custo beneficio da busca tabu
+    ZeroDuration { order: usize },
+    // nunca pode ser selecionada
+    DemandExceedsCapacity { order: usize, demand: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: ", severity)?;
+        match &self.kind {
+            DiagnosticKind::EmptyInstance => write!(f, "instance has no orders"),
+            DiagnosticKind::OrderCountMismatch { declared, found } => write!(
+                f,
+                "instance declares {} orders but holds {}",
+                declared, found
+            ),
+            DiagnosticKind::ZeroStart { order } => {
+                write!(f, "order {} starts at period 0", order)
+            }
+            DiagnosticKind::EndBeforeStart { order, start, end } => write!(
+                f,
+                "order {} ends at {} before its start {}",
+                order, end, start
+            ),
+            DiagnosticKind::ZeroDuration { order } => {
+                write!(f, "order {} starts and ends in the same period", order)
+            }
+            DiagnosticKind::DemandExceedsCapacity { order, demand } => write!(
+                f,
+                "order {} has demand {} above the instance capacity",
+                order, demand
+            ),
+        }
+    }
+}
+
+// Erro devolvido pelos solvers quando a validação encontra diagnosticos de erro
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub instance: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "instance {} is invalid", self.instance)?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl TkpInstance {
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut push = |severity, kind| diagnostics.push(Diagnostic { severity, kind });
+
+        if self.orders.is_empty() {
+            push(Severity::Error, DiagnosticKind::EmptyInstance);
+        }
+
+        if self.order_count != self.orders.len() {
+            push(
+                Severity::Error,
+                DiagnosticKind::OrderCountMismatch {
+                    declared: self.order_count,
+                    found: self.orders.len(),
+                },
+            );
+        }
+
+        for (i, order) in self.orders.iter().enumerate() {
+            if order.start == 0 {
+                push(Severity::Warning, DiagnosticKind::ZeroStart { order: i });
+            }
+
+            if order.end < order.start {
+                push(
+                    Severity::Error,
+                    DiagnosticKind::EndBeforeStart {
+                        order: i,
+                        start: order.start,
+                        end: order.end,
+                    },
+                );
+            } else if order.end == order.start {
+                push(Severity::Warning, DiagnosticKind::ZeroDuration { order: i });
+            }
+
+            if order.demand > self.capacity {
+                push(
+                    Severity::Warning,
+                    DiagnosticKind::DemandExceedsCapacity {
+                        order: i,
+                        demand: order.demand,
+                    },
+                );
+            }
+        }
+
+        diagnostics
+    }
+
+    // Valida a instancia antes de um solver rodar: avisos vão para stderr,
+    // erros são devolvidos
+    pub fn check_valid(&self) -> Result<(), ValidationError> {
+        let (errors, warnings): (Vec<_>, Vec<_>) = self
+            .validate()
+            .into_iter()
+            .partition(|d| d.severity == Severity::Error);
+
+        for warning in &warnings {
+            eprintln!("{}: {}", self.name, warning);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError {
+                instance: self.name.clone(),
+                diagnostics: errors,
+            })
+        }
+    }
+
+    // Remove ordens impossiveis (fim antes do inicio ou demanda acima da capacidade)
+    // e desloca o tempo para que o primeiro periodo seja 1. Uma instancia que começa
+    // em 0 só sobe um periodo se nenhuma ordem terminar em u32::MAX; senão fica como está.
+    // Retorna a nova instancia e, para cada ordem mantida, seu indice original
+    pub fn normalize(&self) -> (TkpInstance, Vec<usize>) {
+        let (kept, orders): (Vec<usize>, Vec<Order>) = self
+            .orders
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.end >= o.start && o.demand <= self.capacity)
+            .map(|(i, o)| (i, *o))
+            .unzip();
+
+        let first_start = orders.iter().map(|o| o.start).min().unwrap_or(1);
+        let shift = |period: u32| match first_start {
+            0 if orders.iter().all(|o| o.end < u32::MAX) => period + 1,
+            0 => period,
+            _ => period - (first_start - 1),
+        };
+        let orders: Vec<Order> = orders
+            .iter()
+            .map(|o| Order {
+                start: shift(o.start),
+                end: shift(o.end),
+                ..*o
+            })
+            .collect();
+
+        let instance = TkpInstance {
+            order_count: orders.len(),
+            orders,
+            ..self.clone()
+        };
+
+        (instance, kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_instances::instance;
+
+    use super::*;
+
+    fn kinds(instance: &TkpInstance) -> Vec<(Severity, DiagnosticKind)> {
+        instance
+            .validate()
+            .into_iter()
+            .map(|d| (d.severity, d.kind))
+            .collect()
+    }
+
+    #[test]
+    fn valid_instance_has_no_diagnostics() {
+        let instance = instance(10, &[(5, 4, 1, 3), (5, 10, 2, 6)]);
+        assert!(kinds(&instance).is_empty());
+        assert!(instance.check_valid().is_ok());
+    }
+
+    #[test]
+    fn reports_each_diagnostic_kind() {
+        let empty = instance(10, &[]);
+        assert_eq!(
+            kinds(&empty),
+            [(Severity::Error, DiagnosticKind::EmptyInstance)]
+        );
+
+        let mut mismatch = instance(10, &[(5, 4, 1, 3)]);
+        mismatch.order_count = 2;
+        assert_eq!(
+            kinds(&mismatch),
+            [(
+                Severity::Error,
+                DiagnosticKind::OrderCountMismatch {
+                    declared: 2,
+                    found: 1
+                }
+            )]
+        );
+
+        let orders = instance(
+            10,
+            &[(5, 4, 0, 3), (5, 4, 6, 2), (5, 4, 7, 7), (5, 11, 1, 3)],
+        );
+        assert_eq!(
+            kinds(&orders),
+            [
+                (Severity::Warning, DiagnosticKind::ZeroStart { order: 0 }),
+                (
+                    Severity::Error,
+                    DiagnosticKind::EndBeforeStart {
+                        order: 1,
+                        start: 6,
+                        end: 2
+                    }
+                ),
+                (Severity::Warning, DiagnosticKind::ZeroDuration { order: 2 }),
+                (
+                    Severity::Warning,
+                    DiagnosticKind::DemandExceedsCapacity {
+                        order: 3,
+                        demand: 11
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_valid_returns_only_errors() {
+        let warnings = instance(10, &[(5, 4, 0, 3), (5, 11, 1, 1)]);
+        assert!(warnings.check_valid().is_ok());
+
+        let err = instance(10, &[(5, 4, 0, 3), (5, 4, 6, 2)])
+            .check_valid()
+            .unwrap_err();
+        assert_eq!(err.diagnostics.len(), 1);
+        assert_eq!(err.diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn normalize_drops_impossible_orders_and_shifts_time() {
+        let original = instance(
+            10,
+            &[(1, 4, 5, 8), (2, 4, 9, 3), (3, 11, 6, 7), (4, 10, 7, 12)],
+        );
+        let (normalized, kept) = original.normalize();
+        assert_eq!(kept, [0, 3]);
+        assert_eq!(normalized.order_count, 2);
+        let periods: Vec<_> = normalized.orders.iter().map(|o| (o.start, o.end)).collect();
+        assert_eq!(periods, [(1, 4), (3, 8)]);
+        for (order, &i) in normalized.orders.iter().zip(&kept) {
+            assert_eq!(order.profit, original.orders[i].profit);
+        }
+    }
+
+    #[test]
+    fn normalize_zero_based_instances() {
+        let (normalized, _) = instance(10, &[(1, 1, 0, 4), (1, 1, 2, 9)]).normalize();
+        let periods: Vec<_> = normalized.orders.iter().map(|o| (o.start, o.end)).collect();
+        assert_eq!(periods, [(1, 5), (3, 10)]);
+
+        // subir um periodo estouraria o fim em u32::MAX
+        let (normalized, kept) = instance(10, &[(1, 1, 0, u32::MAX), (1, 1, 3, 4)]).normalize();
+        assert_eq!(kept, [0, 1]);
+        let periods: Vec<_> = normalized.orders.iter().map(|o| (o.start, o.end)).collect();
+        assert_eq!(periods, [(0, u32::MAX), (3, 4)]);
+    }
+
+    #[test]
+    fn normalize_u32_max_end() {
+        let (normalized, _) = instance(10, &[(1, 1, 5, u32::MAX), (1, 1, 7, 8)]).normalize();
+        let periods: Vec<_> = normalized.orders.iter().map(|o| (o.start, o.end)).collect();
+        assert_eq!(periods, [(1, u32::MAX - 4), (3, 4)]);
+    }
+}
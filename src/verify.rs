@@ -0,0 +1,236 @@
+use std::fmt;
+
+use crate::{
+    parse::{Order, TkpInstance},
+    solution_file::{SolutionFile, SolutionFileError},
+    timeline::Timeline,
+};
+
+// Periodos consecutivos [first_period, last_period] em que as mesmas ordens selecionadas
+// ultrapassam a capacidade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityViolation {
+    pub first_period: u64,
+    pub last_period: u64,
+    pub load: u64,
+    pub overflow: u64,
+    // indices das ordens selecionadas ativas nesses periodos
+    pub active_orders: Vec<usize>,
+}
+
+impl CapacityViolation {
+    pub fn period_count(&self) -> u64 {
+        self.last_period - self.first_period + 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub instance: String,
+    pub capacity: u32,
+    pub selected_count: usize,
+    pub order_count: usize,
+    pub total_profit: u64,
+    // objetivo declarado no arquivo de solução, se houver
+    pub claimed_objective: Option<u64>,
+    pub violations: Vec<CapacityViolation>,
+}
+
+impl VerificationReport {
+    pub fn is_feasible(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn objective_matches(&self) -> bool {
+        self.claimed_objective
+            .is_none_or(|claimed| claimed == self.total_profit)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.is_feasible() && self.objective_matches()
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "instance: {}", self.instance)?;
+        writeln!(
+            f,
+            "selected: {} / {}",
+            self.selected_count, self.order_count
+        )?;
+        match self.claimed_objective {
+            Some(claimed) if claimed != self.total_profit => writeln!(
+                f,
+                "profit: {} (MISMATCH, solution claims {})",
+                self.total_profit, claimed
+            )?,
+            Some(_) => writeln!(f, "profit: {} (matches solution)", self.total_profit)?,
+            None => writeln!(f, "profit: {}", self.total_profit)?,
+        }
+
+        if self.is_feasible() {
+            return writeln!(f, "feasible: yes");
+        }
+
+        writeln!(
+            f,
+            "feasible: no ({} violated periods)",
+            self.violations
+                .iter()
+                .map(CapacityViolation::period_count)
+                .sum::<u64>()
+        )?;
+        for violation in &self.violations {
+            let periods = if violation.first_period == violation.last_period {
+                format!("period {}", violation.first_period)
+            } else {
+                format!(
+                    "periods {}-{}",
+                    violation.first_period, violation.last_period
+                )
+            };
+            writeln!(
+                f,
+                "  {}: load {} > capacity {} (+{}), orders {}",
+                periods,
+                violation.load,
+                self.capacity,
+                violation.overflow,
+                violation
+                    .active_orders
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl TkpInstance {
+    // Verifica uma solução de forma independente dos solvers: recalcula o lucro
+    // e a carga ao longo do tempo a partir apenas das ordens e do vetor de seleção
+    pub fn verify(&self, solution: &SolutionFile) -> Result<VerificationReport, SolutionFileError> {
+        if solution.selected_orders.len() != self.orders.len() {
+            return Err(SolutionFileError::LengthMismatch {
+                expected: self.orders.len(),
+                found: solution.selected_orders.len(),
+            });
+        }
+
+        if let Some(found) = &solution.metadata.fingerprint {
+            let expected = self.fingerprint();
+            if *found != expected {
+                return Err(SolutionFileError::FingerprintMismatch {
+                    expected,
+                    found: found.clone(),
+                });
+            }
+        }
+
+        let selected: Vec<usize> = solution
+            .selected_orders
+            .iter()
+            .enumerate()
+            .filter(|(_, selected)| **selected)
+            .map(|(i, _)| i)
+            .collect();
+
+        let total_profit = selected.iter().map(|i| self.orders[*i].profit as u64).sum();
+
+        // carga por segmento do eixo comprimido das ordens selecionadas: dentro de um
+        // segmento as ordens ativas não mudam, então o custo não depende do horizonte
+        let selected_orders: Vec<Order> = selected.iter().map(|i| self.orders[*i]).collect();
+        let timeline = Timeline::new(&selected_orders);
+        let mut load_delta = vec![0i64; timeline.points.len()];
+        for (order, span) in selected_orders.iter().zip(&timeline.spans) {
+            if !span.is_empty() {
+                load_delta[span.start] += order.demand as i64;
+                load_delta[span.end] -= order.demand as i64;
+            }
+        }
+
+        let mut load = 0i64;
+        let mut violations = Vec::new();
+        for (k, delta) in load_delta.iter().enumerate().take(timeline.segment_count()) {
+            load += delta;
+            let load = load as u64;
+            if load <= self.capacity as u64 {
+                continue;
+            }
+            violations.push(CapacityViolation {
+                first_period: timeline.segment_start(k),
+                last_period: timeline.segment_start(k) + timeline.segment_length(k) - 1,
+                load,
+                overflow: load - self.capacity as u64,
+                active_orders: selected
+                    .iter()
+                    .zip(&timeline.spans)
+                    .filter(|(_, span)| span.contains(&k))
+                    .map(|(i, _)| *i)
+                    .collect(),
+            });
+        }
+
+        Ok(VerificationReport {
+            instance: self.name.clone(),
+            capacity: self.capacity,
+            selected_count: selected.len(),
+            order_count: self.orders.len(),
+            total_profit,
+            claimed_objective: solution.metadata.objective,
+            violations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solution_file::SolutionMetadata;
+
+    use super::*;
+
+    fn check(instance: &str, selected_orders: Vec<bool>) -> VerificationReport {
+        TkpInstance::parse_from_str(instance, "test")
+            .unwrap()
+            .verify(&SolutionFile {
+                metadata: SolutionMetadata::default(),
+                selected_orders,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn reports_violations_per_range() {
+        let report = check("3\n10\n5 6 1 10\n5 6 5 20\n5 3 8 12\n", vec![true; 3]);
+        assert_eq!(report.total_profit, 15);
+        let ranges: Vec<_> = report
+            .violations
+            .iter()
+            .map(|v| {
+                (
+                    v.first_period,
+                    v.last_period,
+                    v.load,
+                    v.active_orders.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(ranges, [(5, 7, 12, vec![0, 1]), (8, 10, 15, vec![0, 1, 2])]);
+        assert!(!report.is_feasible());
+    }
+
+    #[test]
+    fn long_horizon_does_not_allocate_per_period() {
+        let report = check(
+            "2\n1\n1 1 1 4000000000\n1 1 0 4294967295\n",
+            vec![true, true],
+        );
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].first_period, 1);
+        assert_eq!(report.violations[0].last_period, 4000000000);
+        assert_eq!(report.violations[0].period_count(), 4000000000);
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::parse::TkpInstance;
+
+impl TkpInstance {
+    // Escreve a instancia no mesmo formato lido por `parse_from_file`:
+    // n, capacidade e uma linha `lucro demanda inicio fim` por ordem
+    pub fn write_to<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "{}", self.orders.len())?;
+        writeln!(writer, "{}", self.capacity)?;
+        for order in &self.orders {
+            writeln!(
+                writer,
+                "{} {} {} {}",
+                order.profit, order.demand, order.start, order.end
+            )?;
+        }
+        writer.flush()
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> std::io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn to_instance_string(&self) -> String {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)
+            .expect("escrita em memoria não falha");
+        String::from_utf8(buffer).expect("saida é sempre ascii")
+    }
+
+    // Hash FNV-1a de 64 bits do formato de texto da instancia: identifica o conteudo,
+    // independente do nome ou do caminho do arquivo
+    pub fn fingerprint(&self) -> String {
+        let hash = self
+            .to_instance_string()
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+        format!("{:016x}", hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::generate::Preset;
+
+    use super::*;
+
+    fn assert_round_trip(instance: &TkpInstance) {
+        let parsed = TkpInstance::parse_from_str(&instance.to_instance_string(), &instance.name)
+            .unwrap_or_else(|e| panic!("{}: {}", instance.name, e));
+        assert_eq!(&parsed, instance);
+        assert_eq!(parsed.fingerprint(), instance.fingerprint());
+    }
+
+    #[test]
+    fn bundled_instances_round_trip() {
+        let folder = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tkp_instances");
+        let mut count = 0;
+        for entry in std::fs::read_dir(&folder).unwrap() {
+            let instance = TkpInstance::parse_from_file(&entry.unwrap().path()).unwrap();
+            assert_round_trip(&instance);
+            count += 1;
+        }
+        assert_eq!(count, 8);
+    }
+
+    #[test]
+    fn generated_instance_round_trips() {
+        for preset in Preset::ALL {
+            let instance = preset.scaled(300).generate(preset.name(), 11).unwrap();
+            assert_round_trip(&instance);
+        }
+    }
+}